@@ -1,10 +1,14 @@
 use std::path::PathBuf;
 
 use clap::Args;
+use clap::value_parser;
+use url::Url;
 
 use crate::cli::CommandHandler;
 use crate::context::McContext;
+use crate::mods::service::ModServiceKind;
 use crate::ops;
+use crate::ops::mods::AddModSource;
 use crate::ops::mods::AddModsOptions;
 use crate::utils::errors::CliResult;
 
@@ -28,15 +32,54 @@ pub struct AddCommand {
     )]
     pub lockfile_path: PathBuf,
 
-    /// Reference to a mod to add
-    #[arg(required = true, value_name = "MOD_ID")]
+    /// Only edit mc.toml, without updating mc.lock
+    #[arg(long)]
+    pub no_sync: bool,
+
+    /// Mod service to add the mods from (modrinth, curseforge)
+    #[arg(long, value_parser = value_parser!(ModServiceKind), default_value = "modrinth", hide_default_value = true)]
+    pub service: ModServiceKind,
+
+    /// Add the mod from a jar url
+    #[arg(long, value_name = "URL", conflicts_with_all = ["path", "github"])]
+    pub url: Option<Url>,
+
+    /// Add the mod from a jar of the project, relative to mc.toml
+    #[arg(long, value_name = "PATH", conflicts_with = "github")]
+    pub path: Option<PathBuf>,
+
+    /// Add the mod from the latest release of a github repository
+    #[arg(long, value_name = "OWNER/REPO")]
+    pub github: Option<String>,
+
+    /// Reference to a mod to add (ex: sodium or sodium@mc1.21.4-0.6.0), the name of the mod
+    /// when adding from a url, a file or a github repository
+    #[arg(
+        required_unless_present_any = ["url", "path", "github"],
+        value_name = "MOD_ID"
+    )]
     pub mods: Vec<String>
 }
 
+impl AddCommand {
+    fn source(&self) -> Option<AddModSource> {
+        if let Some(ref url) = self.url {
+            Some(AddModSource::Url(url.clone()))
+        } else if let Some(ref path) = self.path {
+            Some(AddModSource::Path(path.clone()))
+        } else {
+            self.github.clone().map(AddModSource::GitHub)
+        }
+    }
+}
+
 impl CommandHandler for AddCommand {
     async fn handle(&self, context: &mut McContext) -> CliResult {
         let options = AddModsOptions {
             mods: self.mods.clone(),
+            source: self.source(),
+            service: self.service,
+            sync: !self.no_sync,
             manifest_path: self.manifest_path.clone(),
             lockfile_path: self.lockfile_path.clone()
         };
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use clap::Subcommand;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::export::ExportMrpackOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct ExportCommand {
+    #[command(subcommand)]
+    pub command: ExportSubcommand
+}
+
+/// Export the project as a modpack
+#[derive(Subcommand)]
+pub enum ExportSubcommand {
+    /// Export a Modrinth modpack (.mrpack)
+    Mrpack(ExportMrpackCommand)
+}
+
+#[derive(Args)]
+pub struct ExportMrpackCommand {
+    /// Version of the modpack
+    #[arg(long, default_value = "1.0.0")]
+    pub version_id: String,
+
+    /// Directory of the instance to include in the modpack, can be repeated
+    #[arg(long = "override", value_name = "DIR", default_value = "config")]
+    pub overrides: Vec<PathBuf>,
+
+    /// Where to write the modpack, defaults to <name>-<version>.mrpack
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for ExportMrpackCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = ExportMrpackOptions {
+            version_id: self.version_id.clone(),
+            overrides: self.overrides.clone(),
+            output: self.output.clone(),
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::export::mrpack(context, &options).await?;
+
+        Ok(())
+    }
+}
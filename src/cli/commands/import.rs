@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use clap::Subcommand;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::import::ImportCurseForgeOptions;
+use crate::ops::import::ImportMrpackOptions;
+use crate::ops::import::ImportPackwizOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct ImportCommand {
+    #[command(subcommand)]
+    pub command: ImportSubcommand
+}
+
+/// Import modpacks into mc.toml
+#[derive(Subcommand)]
+pub enum ImportSubcommand {
+    /// Import a Modrinth modpack (.mrpack)
+    Mrpack(ImportMrpackCommand),
+
+    /// Import a packwiz modpack
+    Packwiz(ImportPackwizCommand),
+
+    /// Import a CurseForge modpack (.zip)
+    Curseforge(ImportCurseForgeCommand)
+}
+
+#[derive(Args)]
+pub struct ImportMrpackCommand {
+    /// Path to a .mrpack file, or the slug of a modpack on modrinth
+    #[arg(value_name = "FILE|SLUG")]
+    pub pack: String,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for ImportMrpackCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = ImportMrpackOptions {
+            pack: self.pack.clone(),
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::import::mrpack(context, &options).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+pub struct ImportPackwizCommand {
+    /// Path to a pack.toml file or its directory, or the url of a pack.toml file
+    #[arg(value_name = "PATH|URL")]
+    pub pack: String,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for ImportPackwizCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = ImportPackwizOptions {
+            pack: self.pack.clone(),
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::import::packwiz(context, &options).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+pub struct ImportCurseForgeCommand {
+    /// Path to a CurseForge modpack .zip file
+    #[arg(value_name = "FILE")]
+    pub pack: PathBuf,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for ImportCurseForgeCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = ImportCurseForgeOptions {
+            pack: self.pack.clone(),
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::import::curseforge(context, &options).await?;
+
+        Ok(())
+    }
+}
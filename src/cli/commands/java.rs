@@ -56,7 +56,8 @@ impl CommandHandler for JavaInstallCommand {
             version,
             platform,
             architecture,
-            java_directory
+            java_directory,
+            checksum: None
         };
 
         ops::java::install(context, &options).await?;
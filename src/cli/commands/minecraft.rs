@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use clap::Subcommand;
 use clap::value_parser;
@@ -6,6 +8,7 @@ use crate::cli::CommandHandler;
 use crate::context::McContext;
 use crate::mods::loader::LoaderKind;
 use crate::ops;
+use crate::ops::minecraft::MinecraftCanUpgradeOptions;
 use crate::ops::minecraft::MinecraftInstallOptions;
 use crate::ops::minecraft::MinecraftListLoadersOptions;
 use crate::ops::minecraft::MinecraftListOptions;
@@ -31,7 +34,10 @@ pub enum MinecraftSubcommand {
     List(MinecraftListCommand),
 
     /// List all available Minecraft loader versions
-    ListLoaders(MinecraftListLoadersCommand)
+    ListLoaders(MinecraftListLoadersCommand),
+
+    /// Check whether every locked mod has a release for a Minecraft version
+    CanUpgrade(MinecraftCanUpgradeCommand)
 }
 
 #[derive(Args)]
@@ -62,7 +68,9 @@ impl CommandHandler for MinecraftInstallCommand {
         let options = MinecraftInstallOptions {
             version,
             loader,
-            minecraft_directory
+            minecraft_directory,
+            installer: None,
+            checksum: None
         };
 
         ops::minecraft::install(context, &options).await?;
@@ -137,3 +145,34 @@ impl CommandHandler for MinecraftListLoadersCommand {
         Ok(())
     }
 }
+
+#[derive(Args)]
+pub struct MinecraftCanUpgradeCommand {
+    /// Minecraft version to upgrade to
+    pub version: String,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for MinecraftCanUpgradeCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let minecraft_version =
+            MinecraftVersionResolver::resolve(context, Some(self.version.as_str())).await?;
+
+        let options = MinecraftCanUpgradeOptions {
+            minecraft_version,
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::minecraft::can_upgrade(context, &options).await?;
+
+        Ok(())
+    }
+}
@@ -1,18 +1,36 @@
 pub mod add;
+pub mod check;
+pub mod export;
+pub mod import;
 pub mod init;
 pub mod java;
 pub mod minecraft;
+pub mod mods;
+pub mod outdated;
 pub mod remove;
 pub mod run;
+pub mod search;
+pub mod tree;
+pub mod update;
+pub mod why;
 
 use clap::Subcommand;
 
 use crate::cli::commands::add::AddCommand;
+use crate::cli::commands::check::CheckCommand;
+use crate::cli::commands::export::ExportCommand;
+use crate::cli::commands::import::ImportCommand;
 use crate::cli::commands::init::InitCommand;
 use crate::cli::commands::java::JavaCommand;
 use crate::cli::commands::minecraft::MinecraftCommand;
+use crate::cli::commands::mods::ModsCommand;
+use crate::cli::commands::outdated::OutdatedCommand;
 use crate::cli::commands::remove::RemoveCommand;
 use crate::cli::commands::run::RunCommand;
+use crate::cli::commands::search::SearchCommand;
+use crate::cli::commands::tree::TreeCommand;
+use crate::cli::commands::update::UpdateCommand;
+use crate::cli::commands::why::WhyCommand;
 
 #[derive(Subcommand)]
 pub enum CliCommand {
@@ -26,11 +44,35 @@ pub enum CliCommand {
     /// Run the Minecraft instance
     Run(RunCommand),
 
+    /// Validate mc.toml against the locked Minecraft version
+    Check(CheckCommand),
+
     // Add mods to a manifest file
     Add(AddCommand),
 
     // Remove mods from a manifest file
-    Remove(RemoveCommand)
+    Remove(RemoveCommand),
+
+    Mods(ModsCommand),
+
+    /// Update the versions recorded in mc.lock
+    Update(UpdateCommand),
+
+    /// Show the locked versions that could be upgraded
+    Outdated(OutdatedCommand),
+
+    Import(ImportCommand),
+
+    Export(ExportCommand),
+
+    /// Search modrinth for mods compatible with the project
+    Search(SearchCommand),
+
+    /// Show the locked mod dependency graph
+    Tree(TreeCommand),
+
+    /// Show why a mod is installed
+    Why(WhyCommand)
 }
 
 // TODO: use refs + lifetime in the option structs to avoid cloning the cli args.
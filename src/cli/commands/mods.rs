@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use clap::Subcommand;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::mods::AdoptModsOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct ModsCommand {
+    #[command(subcommand)]
+    pub command: ModsSubcommand
+}
+
+/// Manage the mods installed in the instance
+#[derive(Subcommand)]
+pub enum ModsSubcommand {
+    /// Add the jars that are not managed by mc.toml to it, when they are found on modrinth
+    Adopt(ModsAdoptCommand)
+}
+
+#[derive(Args)]
+pub struct ModsAdoptCommand {
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for ModsAdoptCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = AdoptModsOptions {
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::mods::adopt(context, &options).await?;
+
+        Ok(())
+    }
+}
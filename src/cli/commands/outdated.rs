@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::outdated::OutdatedOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct OutdatedCommand {
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf,
+
+    /// Output the report as json
+    #[arg(long)]
+    pub json: bool
+}
+
+impl CommandHandler for OutdatedCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = OutdatedOptions {
+            json: self.json,
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::outdated::outdated(context, &options).await?;
+
+        Ok(())
+    }
+}
@@ -28,6 +28,10 @@ pub struct RemoveCommand {
     )]
     pub lockfile_path: PathBuf,
 
+    /// Only edit mc.toml, without updating mc.lock
+    #[arg(long)]
+    pub no_sync: bool,
+
     /// Reference to a mod to remove
     #[arg(required = true, value_name = "MOD_ID")]
     pub mods: Vec<String>
@@ -37,6 +41,7 @@ impl CommandHandler for RemoveCommand {
     async fn handle(&self, context: &mut McContext) -> CliResult {
         let options = RemoveModsOptions {
             mods: self.mods.clone(),
+            sync: !self.no_sync,
             manifest_path: self.manifest_path.clone(),
             lockfile_path: self.lockfile_path.clone()
         };
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::search::SearchOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct SearchCommand {
+    /// Text to search for
+    pub query: String,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf,
+
+    /// Limit the number of results (default: 10, max: 100)
+    #[arg(long, default_value_t = 10, hide_default_value = true, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub limit: u8,
+
+    /// Pick mods from the results to add to mc.toml
+    #[arg(long)]
+    pub add: bool
+}
+
+impl CommandHandler for SearchCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = SearchOptions {
+            query: self.query.clone(),
+            limit: self.limit as usize,
+            add: self.add,
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::search::search(context, &options).await?;
+
+        Ok(())
+    }
+}
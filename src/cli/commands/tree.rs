@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::tree::TreeOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct TreeCommand {
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for TreeCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = TreeOptions {
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::tree::tree(context, &options).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::update::UpdateOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct UpdateCommand {
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf,
+
+    /// Don't write the lockfile, only print the changes
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Entries to update (a mod, a datapack, `minecraft`, `loader`, `java` or `resource-pack`), defaults to everything
+    #[arg(value_name = "NAME")]
+    pub names: Vec<String>
+}
+
+impl CommandHandler for UpdateCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = UpdateOptions {
+            names: self.names.clone(),
+            dry_run: self.dry_run,
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::update::update(context, &options).await?;
+
+        Ok(())
+    }
+}
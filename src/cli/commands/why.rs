@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::CommandHandler;
+use crate::context::McContext;
+use crate::ops;
+use crate::ops::tree::WhyOptions;
+use crate::utils::errors::CliResult;
+
+#[derive(Args)]
+pub struct WhyCommand {
+    /// Name of the mod
+    pub name: String,
+
+    /// Path to mc.toml
+    #[arg(
+        long,
+        default_value = "./mc.toml",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub manifest_path: PathBuf,
+
+    /// Path to mc.lock
+    #[arg(
+        long,
+        default_value = "./mc.lock",
+        hide_default_value = true,
+        value_name = "PATH"
+    )]
+    pub lockfile_path: PathBuf
+}
+
+impl CommandHandler for WhyCommand {
+    async fn handle(&self, context: &mut McContext) -> CliResult {
+        let options = WhyOptions {
+            name: self.name.clone(),
+            manifest_path: self.manifest_path.clone(),
+            lockfile_path: self.lockfile_path.clone()
+        };
+
+        ops::tree::why(context, &options).await?;
+
+        Ok(())
+    }
+}
@@ -22,5 +22,17 @@ pub struct GlobalOptions {
         hide_possible_values = true,
         ignore_case = true
     )]
-    pub color: ColorChoice
+    pub color: ColorChoice,
+
+    /// Assert that mc.lock will remain unchanged
+    #[arg(long, global = true)]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Equivalent to specifying both --locked and --offline
+    #[arg(long, global = true)]
+    pub frozen: bool
 }
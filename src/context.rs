@@ -14,7 +14,9 @@ use crate::utils::shell::Shell;
 pub struct McContext {
     shell: Mutex<Shell>,
     pub cwd: PathBuf,
-    pub http_client: reqwest::Client
+    pub http_client: reqwest::Client,
+    pub locked: bool,
+    pub offline: bool
 }
 
 impl McContext {
@@ -35,7 +37,9 @@ impl McContext {
         Ok(McContext {
             shell: Mutex::new(shell),
             cwd,
-            http_client
+            http_client,
+            locked: false,
+            offline: false
         })
     }
 
@@ -50,4 +54,25 @@ impl McContext {
     pub fn shell(&self) -> MutexGuard<'_, Shell> {
         self.shell.lock().unwrap()
     }
+
+    /// Fails when `what` would change the lockfile while it is locked.
+    pub fn ensure_unlocked(&self, what: &str) -> McResult<()> {
+        if self.locked {
+            anyhow::bail!(
+                "mc.lock needs to be updated for {}, but --locked was passed to prevent this",
+                what
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fails when `what` needs the network while running offline.
+    pub fn ensure_online(&self, what: &str) -> McResult<()> {
+        if self.offline {
+            anyhow::bail!("{} requires network access, but --offline was passed", what);
+        }
+
+        Ok(())
+    }
 }
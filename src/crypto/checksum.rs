@@ -1,10 +1,49 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
 use url::Url;
 
+use crate::utils::errors::McResult;
+
 #[allow(non_camel_case_types)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ChecksumAlgorithm {
     md5,
     sha1,
-    sha256
+    sha256,
+    sha512
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md5" => Ok(ChecksumAlgorithm::md5),
+            "sha1" => Ok(ChecksumAlgorithm::sha1),
+            "sha256" => Ok(ChecksumAlgorithm::sha256),
+            "sha512" => Ok(ChecksumAlgorithm::sha512),
+            _ => anyhow::bail!("unsupported checksum algorithm {}", s)
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ChecksumAlgorithm::md5 => "md5",
+            ChecksumAlgorithm::sha1 => "sha1",
+            ChecksumAlgorithm::sha256 => "sha256",
+            ChecksumAlgorithm::sha512 => "sha512"
+        };
+
+        write!(f, "{}", s)
+    }
 }
 
 pub enum ChecksumRef {
@@ -13,19 +52,39 @@ pub enum ChecksumRef {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum LocalChecksum {
     md5([u8; 16]),
     sha1([u8; 20]),
-    sha256([u8; 32])
+    sha256([u8; 32]),
+    sha512([u8; 64])
 }
 
 impl LocalChecksum {
+    pub fn from_digest(algorithm: ChecksumAlgorithm, digest: &[u8]) -> McResult<LocalChecksum> {
+        let checksum = match algorithm {
+            ChecksumAlgorithm::md5 => LocalChecksum::md5(digest.try_into()?),
+            ChecksumAlgorithm::sha1 => LocalChecksum::sha1(digest.try_into()?),
+            ChecksumAlgorithm::sha256 => LocalChecksum::sha256(digest.try_into()?),
+            ChecksumAlgorithm::sha512 => LocalChecksum::sha512(digest.try_into()?)
+        };
+
+        Ok(checksum)
+    }
+
+    pub fn from_hex(algorithm: ChecksumAlgorithm, data: &str) -> McResult<LocalChecksum> {
+        let digest = hex::decode(data.trim()).context("could not decode checksum")?;
+
+        LocalChecksum::from_digest(algorithm, &digest)
+            .with_context(|| format!("invalid {} checksum {}", algorithm, data))
+    }
+
     pub fn algorithm(&self) -> ChecksumAlgorithm {
         match self {
             LocalChecksum::md5(_) => ChecksumAlgorithm::md5,
             LocalChecksum::sha1(_) => ChecksumAlgorithm::sha1,
-            LocalChecksum::sha256(_) => ChecksumAlgorithm::sha256
+            LocalChecksum::sha256(_) => ChecksumAlgorithm::sha256,
+            LocalChecksum::sha512(_) => ChecksumAlgorithm::sha512
         }
     }
 
@@ -33,11 +92,50 @@ impl LocalChecksum {
         match self {
             LocalChecksum::md5(data) => data,
             LocalChecksum::sha1(data) => data,
-            LocalChecksum::sha256(data) => data
+            LocalChecksum::sha256(data) => data,
+            LocalChecksum::sha512(data) => data
         }
     }
 }
 
+impl FromStr for LocalChecksum {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, data) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("could not parse checksum {}", s))?;
+
+        LocalChecksum::from_hex(algorithm.parse()?, data)
+    }
+}
+
+impl fmt::Display for LocalChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm(), hex::encode(self.hash()))
+    }
+}
+
+impl Serialize for LocalChecksum {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for LocalChecksum {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+
+        LocalChecksum::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 pub struct RemoteChecksum {
     pub url: Url,
     pub algorithm: ChecksumAlgorithm
@@ -65,3 +163,25 @@ impl RemoteChecksum {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_checksum_round_trips_through_its_string_form() {
+        let s = "sha1:a9993e364706816aba3e25717850c26c9cd0d89d";
+        let checksum = LocalChecksum::from_str(s).unwrap();
+
+        assert_eq!(checksum.algorithm(), ChecksumAlgorithm::sha1);
+        assert_eq!(checksum.to_string(), s);
+    }
+
+    #[test]
+    fn local_checksum_rejects_a_digest_of_the_wrong_length() {
+        assert!(
+            LocalChecksum::from_str("sha256:a9993e364706816aba3e25717850c26c9cd0d89d").is_err()
+        );
+        assert!(LocalChecksum::from_str("a9993e364706816aba3e25717850c26c9cd0d89d").is_err());
+    }
+}
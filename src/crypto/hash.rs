@@ -1,4 +1,5 @@
 use std::io;
+use std::path::Path;
 use std::pin;
 use std::task;
 
@@ -7,13 +8,17 @@ use digest::DynDigest;
 use md5::Md5;
 use sha1::Sha1;
 use sha2::Sha256;
+use sha2::Sha512;
 use tokio::io::AsyncRead;
 use tokio::io::ReadBuf;
 
 use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
+use crate::utils::errors::McResult;
 
 pub struct Hasher<R> {
     reader: R,
+    algorithm: ChecksumAlgorithm,
     digest: Box<dyn DynDigest>
 }
 
@@ -22,15 +27,24 @@ impl<R> Hasher<R> {
         let digest: Box<dyn DynDigest> = match checksum {
             ChecksumAlgorithm::md5 => Box::new(Md5::new()),
             ChecksumAlgorithm::sha1 => Box::new(Sha1::new()),
-            ChecksumAlgorithm::sha256 => Box::new(Sha256::new())
+            ChecksumAlgorithm::sha256 => Box::new(Sha256::new()),
+            ChecksumAlgorithm::sha512 => Box::new(Sha512::new())
         };
 
-        Hasher { reader, digest }
+        Hasher {
+            reader,
+            algorithm: checksum,
+            digest
+        }
     }
 
     pub fn hash(&self) -> Box<[u8]> {
         self.digest.box_clone().finalize()
     }
+
+    pub fn checksum(&self) -> McResult<LocalChecksum> {
+        LocalChecksum::from_digest(self.algorithm, &self.hash())
+    }
 }
 
 impl<R: io::Read> io::Read for Hasher<R> {
@@ -65,3 +79,73 @@ impl<R: AsyncRead + Unpin> AsyncRead for Hasher<R> {
         poll
     }
 }
+
+pub async fn hash_file(path: &Path, algorithm: ChecksumAlgorithm) -> McResult<LocalChecksum> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut hasher = Hasher::new(file, algorithm);
+
+    tokio::io::copy(&mut hasher, &mut tokio::io::sink()).await?;
+
+    hasher.checksum()
+}
+
+pub fn hash_bytes(bytes: &[u8], algorithm: ChecksumAlgorithm) -> McResult<LocalChecksum> {
+    let mut hasher = Hasher::new(bytes, algorithm);
+
+    io::copy(&mut hasher, &mut io::sink())?;
+
+    hasher.checksum()
+}
+
+/// The version 5 uuid of `url` in the url namespace (RFC 4122), the same url always gives the
+/// same uuid.
+pub fn url_uuid(url: &str) -> String {
+    const URL_NAMESPACE: [u8; 16] = [
+        0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30,
+        0xc8
+    ];
+
+    let digest = Digest::finalize(Sha1::new_with_prefix(URL_NAMESPACE).chain_update(url));
+
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex::encode(&bytes[0..4]),
+        hex::encode(&bytes[4..6]),
+        hex::encode(&bytes[6..8]),
+        hex::encode(&bytes[8..10]),
+        hex::encode(&bytes[10..16])
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_uuid_is_a_version_5_uuid_of_the_url_namespace() {
+        // uuid.uuid5(uuid.NAMESPACE_URL, ...) in python
+        assert_eq!(
+            url_uuid("https://modrinth.com/resourcepack/x"),
+            "eda6e616-ad4c-58da-a77b-3656ec74369b"
+        );
+        assert_eq!(
+            url_uuid("https://example.com/pack.zip"),
+            "6f8c6d76-1e12-5117-9434-9d67f3f0f0bf"
+        );
+    }
+
+    #[test]
+    fn hash_bytes_matches_the_digest() {
+        let checksum = hash_bytes(b"abc", ChecksumAlgorithm::sha256).unwrap();
+
+        assert_eq!(
+            checksum.to_string(),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
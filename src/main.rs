@@ -21,8 +21,11 @@ use tracing::debug;
 use crate::cli::Cli;
 use crate::cli::CommandHandler;
 use crate::cli::commands::CliCommand;
+use crate::cli::commands::export::ExportSubcommand;
+use crate::cli::commands::import::ImportSubcommand;
 use crate::cli::commands::java::JavaSubcommand;
 use crate::cli::commands::minecraft::MinecraftSubcommand;
+use crate::cli::commands::mods::ModsSubcommand;
 use crate::context::McContext;
 use crate::utils::errors::CliError;
 use crate::utils::errors::CliResult;
@@ -63,6 +66,9 @@ async fn main() {
     let color_choice = cli.globals.color;
     context.shell().set_color_choice(color_choice);
 
+    context.locked = cli.globals.locked || cli.globals.frozen;
+    context.offline = cli.globals.offline || cli.globals.frozen;
+
     match run(&cli, &mut context).await {
         Err(e) => exit_with_error(e, &mut context.shell()),
         Ok(()) => {}
@@ -73,17 +79,35 @@ async fn run(cli: &Cli, context: &mut McContext) -> CliResult {
     match &cli.command {
         CliCommand::Init(command) => command.handle(context).await,
         CliCommand::Run(command) => command.handle(context).await,
+        CliCommand::Check(command) => command.handle(context).await,
         CliCommand::Minecraft(command) => match &command.command {
             MinecraftSubcommand::Install(command) => command.handle(context).await,
             MinecraftSubcommand::List(command) => command.handle(context).await,
-            MinecraftSubcommand::ListLoaders(command) => command.handle(context).await
+            MinecraftSubcommand::ListLoaders(command) => command.handle(context).await,
+            MinecraftSubcommand::CanUpgrade(command) => command.handle(context).await
         },
         CliCommand::Java(command) => match &command.command {
             JavaSubcommand::Install(command) => command.handle(context).await,
             JavaSubcommand::List(command) => command.handle(context).await
         },
+        CliCommand::Import(command) => match &command.command {
+            ImportSubcommand::Mrpack(command) => command.handle(context).await,
+            ImportSubcommand::Packwiz(command) => command.handle(context).await,
+            ImportSubcommand::Curseforge(command) => command.handle(context).await
+        },
+        CliCommand::Export(command) => match &command.command {
+            ExportSubcommand::Mrpack(command) => command.handle(context).await
+        },
         CliCommand::Add(command) => command.handle(context).await,
-        CliCommand::Remove(command) => command.handle(context).await
+        CliCommand::Remove(command) => command.handle(context).await,
+        CliCommand::Mods(command) => match &command.command {
+            ModsSubcommand::Adopt(command) => command.handle(context).await
+        },
+        CliCommand::Update(command) => command.handle(context).await,
+        CliCommand::Outdated(command) => command.handle(context).await,
+        CliCommand::Search(command) => command.handle(context).await,
+        CliCommand::Tree(command) => command.handle(context).await,
+        CliCommand::Why(command) => command.handle(context).await
     }
 }
 
@@ -1,4 +1,6 @@
 use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context;
@@ -8,19 +10,169 @@ use serde::Serialize;
 use serde::Serializer;
 use url::Url;
 
+use crate::crypto::checksum::LocalChecksum;
+use crate::crypto::hash;
+use crate::mods::loader::LoaderKind;
+use crate::mods::service::ModServiceKind;
+use crate::utils::errors::McResult;
+use crate::utils::product_descriptor::ProductDescriptor;
 use crate::utils::product_descriptor::RawProductDescriptor;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ModLockfile {
-    pub mods: Vec<ModLockfileEntry>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minecraft: Option<MinecraftLockfileEntry>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java: Option<JavaLockfileEntry>,
+
+    #[serde(default)]
+    pub mods: Vec<ModLockfileEntry>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub datapacks: Vec<DatapackLockfileEntry>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_pack: Option<ResourcePackLockfileEntry>
+}
+
+impl Lockfile {
+    /// Reads the lockfile at `path`, a missing lockfile is considered empty.
+    pub async fn read(path: &Path) -> McResult<Lockfile> {
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+
+        let s = tokio::fs::read_to_string(path)
+            .await
+            .context("could not read mc.lock file")?;
+
+        toml::from_str::<Lockfile>(&s).context("could not parse mc.lock file")
+    }
+
+    /// Drops the mods, datapacks and resource pack resolved through a mod service when they were
+    /// locked for another game version or loader, they will be resolved again on the next sync.
+    pub fn invalidate_mods(&mut self, version: &str, loader: Option<LoaderKind>) {
+        if let Some(ref locked) = self.minecraft
+            && !locked.is_compatible(version, loader)
+        {
+            self.mods.retain(|m| m.source.service().is_none());
+            self.datapacks.retain(|d| d.source.service().is_none());
+
+            if self
+                .resource_pack
+                .as_ref()
+                .is_some_and(|p| p.project.is_some())
+            {
+                self.resource_pack = None;
+            }
+        }
+    }
+
+    pub async fn write(&self, path: &Path) -> McResult<()> {
+        let s = toml::to_string_pretty(self)?;
+
+        tokio::fs::write(path, s)
+            .await
+            .context("could not write mc.lock file")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinecraftLockfileEntry {
+    pub version: String,
+    pub loader: Option<String>,
+    pub installer: Option<String>,
+    pub hash: Option<LocalChecksum>
+}
+
+impl MinecraftLockfileEntry {
+    /// Whether the locked game version satisfies the version requested in the manifest.
+    pub fn satisfies_version(&self, requested: Option<&str>) -> bool {
+        match requested {
+            None | Some("latest") | Some("latest-snapshot") => true,
+            Some(version) => version == self.version
+        }
+    }
+
+    /// Returns the locked loader descriptor, if any.
+    pub fn loader_descriptor(&self) -> McResult<Option<ProductDescriptor<LoaderKind>>> {
+        let Some(ref loader) = self.loader else {
+            return Ok(None);
+        };
+
+        let locked = RawProductDescriptor::from_str(loader)?;
+
+        let Some(version) = locked.version else {
+            return Ok(None);
+        };
+
+        Ok(Some(ProductDescriptor {
+            product: locked.product.parse()?,
+            version
+        }))
+    }
+
+    /// Returns the locked loader when it satisfies the loader requested in the manifest.
+    pub fn locked_loader(
+        &self,
+        requested: &RawProductDescriptor
+    ) -> McResult<Option<ProductDescriptor<LoaderKind>>> {
+        let Some(locked) = self.loader_descriptor()? else {
+            return Ok(None);
+        };
+
+        let satisfied = locked.product.to_string() == requested.product
+            && match requested.version.as_deref() {
+                None | Some("latest") => true,
+                Some(v) => v == locked.version
+            };
+
+        Ok(satisfied.then_some(locked))
+    }
+
+    /// Whether mods locked with this entry can still be used with the given game version and
+    /// loader.
+    pub fn is_compatible(&self, version: &str, loader: Option<LoaderKind>) -> bool {
+        let locked_loader = self.loader_descriptor().ok().flatten().map(|l| l.product);
+
+        self.version == version && locked_loader == loader
+    }
+
+    /// Returns the locked installer when it was used with `loader`.
+    pub fn locked_installer(&self, loader: &ProductDescriptor<LoaderKind>) -> Option<String> {
+        if self.loader.as_deref() == Some(loader.to_string().as_str()) {
+            self.installer.clone()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JavaLockfileEntry {
+    pub version: String,
+    pub hash: Option<LocalChecksum>
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModLockfileEntry {
     pub name: String,
     pub version: Option<String>,
+    /// The version of an entry resolved through a mod service, as its authors name it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_number: Option<String>,
     pub source: ModLockfileSource,
-    pub hash: Option<String>
+    pub hash: Option<LocalChecksum>,
+    /// Where the jar of an entry resolved through a mod service is downloaded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+    /// Mods this entry requires.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    /// Optional dependencies of this entry that were opted in to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub optional_dependencies: Vec<String>
 }
 
 impl ModLockfileEntry {
@@ -30,12 +182,92 @@ impl ModLockfileEntry {
             version: self.version.clone()
         }
     }
+
+    /// The jar of the entry in the mods folder, named after its descriptor.
+    pub fn file_name(&self) -> String {
+        format!("{}.jar", self.descriptor())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatapackLockfileEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub source: ModLockfileSource,
+    /// Local directories are copied on every sync and have no hash.
+    pub hash: Option<LocalChecksum>,
+    /// Where the zip of a modrinth entry is downloaded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourcePackLockfileEntry {
+    /// The modrinth project of the pack, `None` for packs given as a url.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub url: Url,
+    /// The sha1 of the pack, clients use it to verify their download.
+    pub hash: LocalChecksum
+}
+
+impl ResourcePackLockfileEntry {
+    /// A uuid that stays the same as long as the pack comes from the same project or url, clients
+    /// use it to tell packs apart.
+    pub fn id(&self) -> String {
+        match self.project {
+            Some(ref project) => {
+                hash::url_uuid(&format!("https://modrinth.com/resourcepack/{}", project))
+            }
+            None => hash::url_uuid(self.url.as_str())
+        }
+    }
+}
+
+impl DatapackLockfileEntry {
+    pub fn file_name(&self) -> String {
+        datapack_file_name(&self.name, &self.source)
+    }
+}
+
+/// Local directories are copied into the datapacks folder as is, everything else is a zip file.
+pub fn datapack_file_name(name: &str, source: &ModLockfileSource) -> String {
+    match source {
+        ModLockfileSource::Path(path) if path.extension().is_none_or(|e| e != "zip") => {
+            name.to_string()
+        }
+        _ => format!("{}.zip", name)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModLockfileSource {
     Modrinth,
-    Url(Url)
+    CurseForge,
+    Url(Url),
+    Path(PathBuf)
+}
+
+impl ModLockfileSource {
+    /// The mod service that resolved the entry, `None` for mods from a url or a file.
+    pub fn service(&self) -> Option<ModServiceKind> {
+        match self {
+            ModLockfileSource::Modrinth => Some(ModServiceKind::Modrinth),
+            ModLockfileSource::CurseForge => Some(ModServiceKind::CurseForge),
+            ModLockfileSource::Url(_) | ModLockfileSource::Path(_) => None
+        }
+    }
+}
+
+impl From<ModServiceKind> for ModLockfileSource {
+    fn from(service: ModServiceKind) -> Self {
+        match service {
+            ModServiceKind::Modrinth => ModLockfileSource::Modrinth,
+            ModServiceKind::CurseForge => ModLockfileSource::CurseForge
+        }
+    }
 }
 
 impl Serialize for ModLockfileSource {
@@ -64,8 +296,10 @@ impl FromStr for ModLockfileSource {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "modrinth" {
-            return Ok(ModLockfileSource::Modrinth);
+        match s {
+            "modrinth" => return Ok(ModLockfileSource::Modrinth),
+            "curseforge" => return Ok(ModLockfileSource::CurseForge),
+            _ => {}
         }
 
         let (prefix, data) = s
@@ -77,6 +311,7 @@ impl FromStr for ModLockfileSource {
                 let url = Url::parse(data)?;
                 Ok(ModLockfileSource::Url(url))
             }
+            "path" => Ok(ModLockfileSource::Path(PathBuf::from(data))),
             _ => anyhow::bail!("unsupported prefix {} in lockfile source", prefix)
         }
     }
@@ -86,9 +321,138 @@ impl fmt::Display for ModLockfileSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
             ModLockfileSource::Modrinth => "modrinth".to_string(),
-            ModLockfileSource::Url(url) => format!("url+{}", url)
+            ModLockfileSource::CurseForge => "curseforge".to_string(),
+            ModLockfileSource::Url(url) => format!("url+{}", url),
+            ModLockfileSource::Path(path) => format!("path+{}", path.display())
         };
 
         write!(f, "{}", s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+[minecraft]
+version = "1.21.1"
+loader = "fabric@0.16.5"
+installer = "1.0.1"
+hash = "sha1:a9993e364706816aba3e25717850c26c9cd0d89d"
+
+[[mods]]
+name = "sodium"
+version = "abcd1234"
+source = "modrinth"
+hash = "sha512:ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+url = "https://cdn.modrinth.com/data/AANobbMI/versions/abcd1234/sodium.jar"
+dependencies = ["fabric-api"]
+
+[[mods]]
+name = "local"
+source = "path+mods/local.jar"
+hash = "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+
+[[datapacks]]
+name = "terralith"
+version = "efgh5678"
+source = "modrinth"
+hash = "sha1:a9993e364706816aba3e25717850c26c9cd0d89d"
+url = "https://cdn.modrinth.com/data/8oi3bsk5/versions/efgh5678/terralith.zip"
+
+[resource_pack]
+url = "https://example.com/pack.zip"
+hash = "sha1:a9993e364706816aba3e25717850c26c9cd0d89d"
+"#;
+
+    #[test]
+    fn lockfile_round_trips() {
+        let lockfile = toml::from_str::<Lockfile>(LOCKFILE).unwrap();
+        let written = toml::to_string_pretty(&lockfile).unwrap();
+
+        assert_eq!(toml::from_str::<Lockfile>(&written).unwrap(), lockfile);
+        assert_eq!(
+            lockfile.mods[1].source,
+            ModLockfileSource::Path(PathBuf::from("mods/local.jar"))
+        );
+        assert_eq!(lockfile.mods[0].dependencies, vec!["fabric-api"]);
+    }
+
+    #[test]
+    fn lockfile_sources_round_trip() {
+        for s in [
+            "modrinth",
+            "curseforge",
+            "url+https://example.com/mod.jar",
+            "path+mods/local.jar"
+        ] {
+            assert_eq!(ModLockfileSource::from_str(s).unwrap().to_string(), s);
+        }
+
+        assert!(ModLockfileSource::from_str("git+https://example.com").is_err());
+    }
+
+    #[test]
+    fn datapack_file_names() {
+        assert_eq!(
+            datapack_file_name("terralith", &ModLockfileSource::Modrinth),
+            "terralith.zip"
+        );
+        assert_eq!(
+            datapack_file_name(
+                "pack",
+                &ModLockfileSource::Url(Url::parse("https://example.com/pack").unwrap())
+            ),
+            "pack.zip"
+        );
+        assert_eq!(
+            datapack_file_name(
+                "pack",
+                &ModLockfileSource::Path(PathBuf::from("packs/pack.zip"))
+            ),
+            "pack.zip"
+        );
+        assert_eq!(
+            datapack_file_name(
+                "pack",
+                &ModLockfileSource::Path(PathBuf::from("packs/pack"))
+            ),
+            "pack"
+        );
+    }
+
+    #[test]
+    fn mod_jars_keep_dots_in_their_name() {
+        let entry = ModLockfileEntry {
+            name: String::from("lithium-0.12.1"),
+            version: None,
+            version_number: None,
+            source: ModLockfileSource::Url(Url::parse("https://example.com/lithium.jar").unwrap()),
+            hash: None,
+            url: None,
+            dependencies: Vec::new(),
+            optional_dependencies: Vec::new()
+        };
+
+        let file_name = entry.file_name();
+
+        assert_eq!(file_name, "lithium-0.12.1.jar");
+        assert_eq!(
+            Path::new(&file_name).file_stem().unwrap(),
+            entry.descriptor().to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn resource_pack_ids_are_stable() {
+        let lockfile = toml::from_str::<Lockfile>(LOCKFILE).unwrap();
+        let mut pack = lockfile.resource_pack.unwrap();
+
+        assert_eq!(pack.id(), "6f8c6d76-1e12-5117-9434-9d67f3f0f0bf");
+
+        pack.project = Some(String::from("x"));
+
+        assert_eq!(pack.id(), "eda6e616-ad4c-58da-a77b-3656ec74369b");
+    }
+}
@@ -2,15 +2,19 @@ pub mod lock;
 pub mod presets;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use url::Url;
 
 use crate::context::McContext;
 use crate::java::JavaDescriptor;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileSource;
 use crate::minecraft::MinecraftDifficulty;
 use crate::minecraft::MinecraftGamemode;
 use crate::minecraft::MinecraftLevelKind;
+use crate::minecraft::gamerules::MinecraftGameruleValue;
 use crate::minecraft::seed::MinecraftSeed;
 use crate::mods::loader::LoaderKind;
 use crate::mods::service::ModServiceKind;
@@ -39,6 +43,12 @@ pub struct Manifest {
     #[serde(default)]
     pub mods: HashMap<String, ManifestMod>,
 
+    #[serde(default)]
+    pub datapacks: HashMap<String, ManifestDatapack>,
+
+    #[serde(default)]
+    pub gamerules: HashMap<String, MinecraftGameruleValue>,
+
     #[serde(default)]
     pub backups: ManifestBackups
 }
@@ -49,10 +59,188 @@ pub enum ManifestMod {
     Version(String),
     Detailed {
         version: String,
-        service: ModServiceKind
+
+        #[serde(default)]
+        service: ModServiceKind,
+
+        /// Optional dependencies to install with this mod
+        #[serde(default)]
+        optional: ManifestOptionalDependencies,
+
+        /// Mods declared as incompatible that are known to work together with this one
+        #[serde(default)]
+        allow_incompatible: Vec<String>
     },
     Remote {
         url: Url
+    },
+    Local {
+        path: PathBuf
+    }
+}
+
+impl ManifestMod {
+    /// Returns the pinned version id, `None` when any compatible version is accepted (`*` or
+    /// `latest`).
+    pub fn pinned_version(&self) -> Option<&str> {
+        let version = match self {
+            ManifestMod::Version(version) => version,
+            ManifestMod::Detailed { version, .. } => version,
+            ManifestMod::Remote { .. } | ManifestMod::Local { .. } => return None
+        };
+
+        match version.as_str() {
+            "*" | "latest" => None,
+            v => Some(v)
+        }
+    }
+
+    /// The lockfile source of mods that are not resolved from a mod service.
+    pub fn direct_source(&self) -> Option<ModLockfileSource> {
+        match self {
+            ManifestMod::Remote { url } => Some(ModLockfileSource::Url(url.clone())),
+            ManifestMod::Local { path } => Some(ModLockfileSource::Path(path.clone())),
+            _ => None
+        }
+    }
+
+    /// The mod service this mod is resolved from, `None` for mods from a url or a file.
+    pub fn service(&self) -> Option<ModServiceKind> {
+        match self {
+            ManifestMod::Version(_) => Some(ModServiceKind::Modrinth),
+            ManifestMod::Detailed { service, .. } => Some(*service),
+            ManifestMod::Remote { .. } | ManifestMod::Local { .. } => None
+        }
+    }
+
+    /// Whether the optional dependency `name` of this mod should be installed.
+    pub fn includes_optional(&self, name: &str) -> bool {
+        match self {
+            ManifestMod::Detailed { optional, .. } => optional.includes(name),
+            _ => false
+        }
+    }
+
+    /// Whether any optional dependency of this mod should be installed.
+    pub fn has_optional(&self) -> bool {
+        match self {
+            ManifestMod::Detailed { optional, .. } => !optional.is_empty(),
+            _ => false
+        }
+    }
+
+    /// Whether the manifest acknowledges that this mod, or a dependency it pulls in, works with
+    /// `name` even though one of them declares the other as incompatible.
+    pub fn allows_incompatible(&self, name: &str) -> bool {
+        match self {
+            ManifestMod::Detailed {
+                allow_incompatible, ..
+            } => allow_incompatible.iter().any(|n| n == name),
+            _ => false
+        }
+    }
+}
+
+/// Either `true` to install every optional dependency, or the list of dependencies to install.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ManifestOptionalDependencies {
+    All(bool),
+    Only(Vec<String>)
+}
+
+impl ManifestOptionalDependencies {
+    pub fn includes(&self, name: &str) -> bool {
+        match self {
+            ManifestOptionalDependencies::All(all) => *all,
+            ManifestOptionalDependencies::Only(names) => names.iter().any(|n| n == name)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ManifestOptionalDependencies::All(all) => !all,
+            ManifestOptionalDependencies::Only(names) => names.is_empty()
+        }
+    }
+}
+
+impl Default for ManifestOptionalDependencies {
+    fn default() -> Self {
+        ManifestOptionalDependencies::All(false)
+    }
+}
+
+/// A datapack installed in the world, from modrinth, a url or a local directory or zip file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ManifestDatapack {
+    Version(String),
+    Detailed {
+        version: String,
+
+        #[serde(default = "default_enabled")]
+        enabled: bool
+    },
+    Remote {
+        url: Url,
+
+        #[serde(default = "default_enabled")]
+        enabled: bool
+    },
+    Local {
+        path: PathBuf,
+
+        #[serde(default = "default_enabled")]
+        enabled: bool
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ManifestDatapack {
+    /// Returns the pinned modrinth version id, `None` when any compatible version is accepted or
+    /// when the datapack does not come from modrinth.
+    pub fn pinned_version(&self) -> Option<&str> {
+        let version = match self {
+            ManifestDatapack::Version(version) => version,
+            ManifestDatapack::Detailed { version, .. } => version,
+            ManifestDatapack::Remote { .. } | ManifestDatapack::Local { .. } => return None
+        };
+
+        match version.as_str() {
+            "*" | "latest" => None,
+            v => Some(v)
+        }
+    }
+
+    /// The lockfile source of the datapack.
+    pub fn source(&self) -> ModLockfileSource {
+        match self {
+            ManifestDatapack::Version(_) | ManifestDatapack::Detailed { .. } => {
+                ModLockfileSource::Modrinth
+            }
+            ManifestDatapack::Remote { url, .. } => ModLockfileSource::Url(url.clone()),
+            ManifestDatapack::Local { path, .. } => ModLockfileSource::Path(path.clone())
+        }
+    }
+
+    /// Whether the datapack starts enabled. Minecraft only reads this when the world is created,
+    /// enable or disable datapacks of an existing world with `/datapack` in the server console.
+    pub fn enabled(&self) -> bool {
+        match self {
+            ManifestDatapack::Version(_) => true,
+            ManifestDatapack::Detailed { enabled, .. }
+            | ManifestDatapack::Remote { enabled, .. }
+            | ManifestDatapack::Local { enabled, .. } => *enabled
+        }
+    }
+
+    /// The name of the datapack in the datapacks folder of the world.
+    pub fn file_name(&self, name: &str) -> String {
+        lock::datapack_file_name(name, &self.source())
     }
 }
 
@@ -103,6 +291,67 @@ impl ManifestMinecraft {
             Ok(None)
         }
     }
+
+    /// Returns the game version locked in `lockfile` when it still satisfies the manifest,
+    /// otherwise resolves it again.
+    pub async fn locked_version(
+        &self,
+        context: &McContext,
+        lockfile: &Lockfile
+    ) -> McResult<String> {
+        if let Some(ref locked) = lockfile.minecraft
+            && locked.satisfies_version(self.version.as_deref())
+        {
+            return Ok(locked.version.clone());
+        }
+
+        context.ensure_unlocked("the minecraft version")?;
+        context.ensure_online("resolving the minecraft version")?;
+
+        self.resolved_version(context).await
+    }
+
+    /// Returns the loader locked in `lockfile` when it still satisfies the manifest, otherwise
+    /// resolves it again.
+    pub async fn locked_loader_descriptor(
+        &self,
+        context: &McContext,
+        lockfile: &Lockfile
+    ) -> McResult<Option<ProductDescriptor<LoaderKind>>> {
+        if let (Some(loader), Some(locked)) = (&self.loader, &lockfile.minecraft)
+            && let Some(descriptor) = locked.locked_loader(loader)?
+        {
+            return Ok(Some(descriptor));
+        }
+
+        if self.loader.is_some() {
+            context.ensure_unlocked("the loader version")?;
+            context.ensure_online("resolving the loader version")?;
+        }
+
+        self.loader_descriptor(context).await
+    }
+
+    /// Returns the installer locked in `lockfile` for `loader`, otherwise resolves the latest one.
+    pub async fn locked_installer_version(
+        &self,
+        context: &McContext,
+        lockfile: &Lockfile,
+        loader: &ProductDescriptor<LoaderKind>
+    ) -> McResult<String> {
+        if let Some(installer) = lockfile
+            .minecraft
+            .as_ref()
+            .and_then(|m| m.locked_installer(loader))
+        {
+            return Ok(installer);
+        }
+
+        context.ensure_unlocked("the loader installer version")?;
+        context.ensure_online("resolving the loader installer version")?;
+
+        LoaderVersionResolver::resolve_installer(context, loader.product).await
+    }
 }
 
 #[derive(Deserialize)]
@@ -119,7 +368,10 @@ pub struct ManifestServer {
     pub rcon_port: u16,
     pub capacity: i32,
     pub view_distance: u8,
-    pub simulation_distance: u8
+    pub simulation_distance: u8,
+
+    #[serde(rename = "resource-pack")]
+    pub resource_pack: Option<ManifestResourcePack>
 }
 
 impl Default for ManifestServer {
@@ -136,7 +388,36 @@ impl Default for ManifestServer {
             rcon_port: 25575,
             capacity: 20,
             view_distance: 16,
-            simulation_distance: 16
+            simulation_distance: 16,
+            resource_pack: None
+        }
+    }
+}
+
+/// The resource pack offered to players, from a url or a modrinth project.
+#[derive(Deserialize)]
+pub struct ManifestResourcePack {
+    /// A direct download, its hash is locked until the url changes. A pack replaced behind the
+    /// same url is rejected by clients, change the url or remove it from mc.lock to lock it again.
+    pub url: Option<Url>,
+    pub project: Option<String>,
+
+    /// A modrinth version id, the newest version for the game version is used when omitted.
+    pub version: Option<String>,
+
+    pub prompt: Option<String>,
+
+    #[serde(default)]
+    pub required: bool
+}
+
+impl ManifestResourcePack {
+    /// The modrinth project of the pack, `None` when it is given as a url.
+    pub fn project(&self) -> McResult<Option<&str>> {
+        match (&self.url, &self.project) {
+            (Some(_), None) => Ok(None),
+            (None, Some(project)) => Ok(Some(project)),
+            _ => anyhow::bail!("the resource pack needs either a `url` or a modrinth `project`")
         }
     }
 }
@@ -146,3 +427,35 @@ impl Default for ManifestServer {
 pub struct ManifestBackups {
     pub enabled: bool
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource_pack(s: &str) -> ManifestResourcePack {
+        toml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn resource_pack_project() {
+        let url = resource_pack(r#"url = "https://example.com/pack.zip""#);
+        assert_eq!(url.project().unwrap(), None);
+
+        let project = resource_pack(r#"project = "faithful-32x""#);
+        assert_eq!(project.project().unwrap(), Some("faithful-32x"));
+    }
+
+    #[test]
+    fn resource_pack_needs_a_url_or_a_project() {
+        let neither = resource_pack("required = true");
+        assert!(neither.project().is_err());
+
+        let both = resource_pack(
+            r#"
+            url = "https://example.com/pack.zip"
+            project = "faithful-32x"
+            "#
+        );
+        assert!(both.project().is_err());
+    }
+}
@@ -83,7 +83,7 @@ fn get_technical_modlist() -> Vec<&'static str> {
     ]
 }
 
-fn create_document_base(name: &str, eula: bool, game_version: &String) -> DocumentMut {
+pub fn create_document_base(name: &str, eula: bool, game_version: &String) -> DocumentMut {
     let mut document = DocumentMut::new();
     document["name"] = value(name);
     document["description"] = value("A Minecraft Server");
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::process::ChildStdin;
+use tokio::process::ChildStdout;
+use tokio::sync::mpsc;
+
+use crate::utils::errors::McResult;
+
+/// How long the server has to answer a command.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The replies of the server to a command it could not parse.
+const COMMAND_ERRORS: &[&str] = &[
+    "Unknown or incomplete command",
+    "Incorrect argument for command"
+];
+
+/// The console of a running server, commands are written to its stdin and answered in its log.
+pub struct ServerConsole {
+    stdin: ChildStdin,
+    lines: mpsc::UnboundedReceiver<String>
+}
+
+impl ServerConsole {
+    pub fn new(stdin: ChildStdin, stdout: ChildStdout) -> ServerConsole {
+        let (sender, lines) = mpsc::unbounded_channel();
+
+        // the log is read until the server stops even once the console is dropped, a full pipe
+        // would block the server.
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stdout).lines();
+
+            while let Ok(Some(line)) = reader.next_line().await {
+                _ = sender.send(line);
+            }
+        });
+
+        ServerConsole { stdin, lines }
+    }
+
+    /// Waits until the server reports it is done loading the world.
+    pub async fn wait_until_ready(&mut self) -> McResult<()> {
+        while let Some(line) = self.lines.recv().await {
+            if line.contains("Done (") && line.contains(")! For help") {
+                return Ok(());
+            }
+        }
+
+        anyhow::bail!("the server stopped before it was ready")
+    }
+
+    /// Sends `command` and returns the first line of the log accepted by `answer`, fails when the
+    /// server rejects the command.
+    pub async fn query<T>(
+        &mut self,
+        command: &str,
+        answer: impl Fn(&str) -> Option<T>
+    ) -> McResult<T> {
+        self.stdin
+            .write_all(format!("{}\n", command).as_bytes())
+            .await?;
+        self.stdin.flush().await?;
+
+        let lines = &mut self.lines;
+
+        tokio::time::timeout(COMMAND_TIMEOUT, async {
+            while let Some(line) = lines.recv().await {
+                if let Some(value) = answer(&line) {
+                    return Ok(value);
+                }
+
+                if COMMAND_ERRORS.iter().any(|e| line.contains(e)) {
+                    anyhow::bail!("the server rejected `{}`: {}", command, line);
+                }
+            }
+
+            anyhow::bail!("the server stopped before answering `{}`", command)
+        })
+        .await
+        .with_context(|| format!("the server did not answer `{}`", command))?
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::minecraft::gamerules::MinecraftGameruleKind::Bool;
+use crate::minecraft::gamerules::MinecraftGameruleKind::Int;
+
+/// The newest release the table of gamerules was checked against, rules of newer versions can't
+/// be verified and are rejected until the table is updated.
+const LAST_KNOWN_VERSION: (u32, u32, u32) = (1, 21, 8);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MinecraftGameruleKind {
+    Bool,
+    Int
+}
+
+/// A vanilla gamerule and the release that introduced it.
+struct MinecraftGamerule {
+    name: &'static str,
+    kind: MinecraftGameruleKind,
+    added: (u32, u32, u32)
+}
+
+const fn rule(
+    name: &'static str,
+    kind: MinecraftGameruleKind,
+    added: (u32, u32, u32)
+) -> MinecraftGamerule {
+    MinecraftGamerule { name, kind, added }
+}
+
+const GAMERULES: &[MinecraftGamerule] = &[
+    rule("allowFireTicksAwayFromPlayer", Bool, (1, 21, 5)),
+    rule("announceAdvancements", Bool, (1, 12, 0)),
+    rule("blockExplosionDropDecay", Bool, (1, 19, 3)),
+    rule("commandBlockOutput", Bool, (1, 4, 2)),
+    rule("commandModificationBlockLimit", Int, (1, 19, 4)),
+    rule("disableElytraMovementCheck", Bool, (1, 9, 0)),
+    rule("disablePlayerMovementCheck", Bool, (1, 21, 2)),
+    rule("disableRaids", Bool, (1, 14, 3)),
+    rule("doDaylightCycle", Bool, (1, 6, 1)),
+    rule("doEntityDrops", Bool, (1, 8, 1)),
+    rule("doFireTick", Bool, (1, 4, 2)),
+    rule("doImmediateRespawn", Bool, (1, 15, 0)),
+    rule("doInsomnia", Bool, (1, 15, 0)),
+    rule("doLimitedCrafting", Bool, (1, 12, 0)),
+    rule("doMobLoot", Bool, (1, 4, 2)),
+    rule("doMobSpawning", Bool, (1, 4, 2)),
+    rule("doPatrolSpawning", Bool, (1, 15, 2)),
+    rule("doTileDrops", Bool, (1, 4, 2)),
+    rule("doTraderSpawning", Bool, (1, 15, 2)),
+    rule("doVinesSpread", Bool, (1, 19, 4)),
+    rule("doWardenSpawning", Bool, (1, 19, 0)),
+    rule("doWeatherCycle", Bool, (1, 11, 0)),
+    rule("drowningDamage", Bool, (1, 15, 0)),
+    rule("enderPearlsVanishOnDeath", Bool, (1, 20, 2)),
+    rule("fallDamage", Bool, (1, 15, 0)),
+    rule("fireDamage", Bool, (1, 15, 0)),
+    rule("forgiveDeadPlayers", Bool, (1, 16, 0)),
+    rule("freezeDamage", Bool, (1, 17, 0)),
+    rule("globalSoundEvents", Bool, (1, 19, 3)),
+    rule("keepInventory", Bool, (1, 4, 2)),
+    rule("lavaSourceConversion", Bool, (1, 19, 3)),
+    rule("locatorBar", Bool, (1, 21, 6)),
+    rule("logAdminCommands", Bool, (1, 8, 0)),
+    rule("maxCommandChainLength", Int, (1, 12, 0)),
+    rule("maxCommandForkCount", Int, (1, 20, 3)),
+    rule("maxEntityCramming", Int, (1, 11, 0)),
+    rule("mobExplosionDropDecay", Bool, (1, 19, 3)),
+    rule("mobGriefing", Bool, (1, 4, 2)),
+    rule("naturalRegeneration", Bool, (1, 6, 1)),
+    rule("playersNetherPortalCreativeDelay", Int, (1, 21, 0)),
+    rule("playersNetherPortalDefaultDelay", Int, (1, 21, 0)),
+    rule("playersSleepingPercentage", Int, (1, 17, 0)),
+    rule("projectilesCanBreakBlocks", Bool, (1, 20, 3)),
+    rule("randomTickSpeed", Int, (1, 8, 0)),
+    rule("reducedDebugInfo", Bool, (1, 8, 0)),
+    rule("sendCommandFeedback", Bool, (1, 8, 0)),
+    rule("showDeathMessages", Bool, (1, 8, 0)),
+    rule("snowAccumulationHeight", Int, (1, 19, 3)),
+    rule("spawnChunkRadius", Int, (1, 20, 5)),
+    rule("spawnRadius", Int, (1, 9, 0)),
+    rule("spectatorsGenerateChunks", Bool, (1, 9, 0)),
+    rule("tntExplodes", Bool, (1, 21, 5)),
+    rule("tntExplosionDropDecay", Bool, (1, 19, 3)),
+    rule("universalAnger", Bool, (1, 16, 0)),
+    rule("waterSourceConversion", Bool, (1, 19, 3))
+];
+
+/// The value of a gamerule, as written in mc.toml.
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(untagged)]
+pub enum MinecraftGameruleValue {
+    Bool(bool),
+    Int(i32)
+}
+
+impl MinecraftGameruleValue {
+    pub fn kind(&self) -> MinecraftGameruleKind {
+        match self {
+            MinecraftGameruleValue::Bool(_) => Bool,
+            MinecraftGameruleValue::Int(_) => Int
+        }
+    }
+}
+
+impl fmt::Display for MinecraftGameruleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinecraftGameruleValue::Bool(value) => write!(f, "{}", value),
+            MinecraftGameruleValue::Int(value) => write!(f, "{}", value)
+        }
+    }
+}
+
+impl fmt::Display for MinecraftGameruleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinecraftGameruleKind::Bool => write!(f, "a boolean"),
+            MinecraftGameruleKind::Int => write!(f, "an integer")
+        }
+    }
+}
+
+/// Whether the rules of `version` are known, snapshots and releases newer than the table can't
+/// be verified.
+pub fn is_known_version(version: &str) -> bool {
+    release(version).is_some_and(|v| v <= LAST_KNOWN_VERSION)
+}
+
+/// The newest release whose gamerules are known, like `1.21.8`.
+pub fn last_known_version() -> String {
+    let (major, minor, patch) = LAST_KNOWN_VERSION;
+
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// The type of the gamerule `name` in `version`, `None` when `version` has no such rule.
+pub fn kind(version: &str, name: &str) -> Option<MinecraftGameruleKind> {
+    let release = release(version)?;
+
+    GAMERULES
+        .iter()
+        .find(|r| r.name == name && r.added <= release)
+        .map(|r| r.kind)
+}
+
+/// Parses release versions like `1.21` or `1.20.4`.
+fn release(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>());
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = match parts.next() {
+        Some(patch) => patch.ok()?,
+        None => 0
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn release_parses_releases_only() {
+        assert_eq!(release("1.21"), Some((1, 21, 0)));
+        assert_eq!(release("1.20.4"), Some((1, 20, 4)));
+        assert_eq!(release("24w14a"), None);
+        assert_eq!(release("1.21-pre1"), None);
+        assert_eq!(release("1.21.1.1"), None);
+    }
+
+    #[test]
+    fn known_versions_stop_at_the_table() {
+        assert!(is_known_version("1.20.1"));
+        assert!(is_known_version("1.21.8"));
+        assert!(!is_known_version("1.21.9"));
+        assert!(!is_known_version("25w31a"));
+    }
+
+    #[test]
+    fn last_known_version_is_known() {
+        assert!(is_known_version(&last_known_version()));
+    }
+
+    #[test]
+    fn kind_depends_on_the_version() {
+        assert!(kind("1.21.1", "keepInventory") == Some(Bool));
+        assert!(kind("1.21.1", "randomTickSpeed") == Some(Int));
+        assert!(kind("1.21.1", "locatorBar").is_none());
+        assert!(kind("1.21.6", "locatorBar") == Some(Bool));
+        assert!(kind("1.21.1", "notARule").is_none());
+        assert!(kind("24w14a", "keepInventory").is_none());
+    }
+
+    #[test]
+    fn values_parse_as_their_kind() {
+        let values: HashMap<String, MinecraftGameruleValue> =
+            toml::from_str("keepInventory = true\nrandomTickSpeed = 3").unwrap();
+
+        assert!(values["keepInventory"].kind() == Bool);
+        assert_eq!(values["randomTickSpeed"].to_string(), "3");
+    }
+}
@@ -2,7 +2,9 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde::Serializer;
 
+pub mod console;
 pub mod eula;
+pub mod gamerules;
 pub mod seed;
 pub mod server_properties;
 
@@ -5,6 +5,7 @@ use url::Url;
 
 use crate::exit_with_error;
 use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
 use crate::minecraft::MinecraftDifficulty;
 use crate::minecraft::MinecraftGamemode;
 use crate::minecraft::MinecraftLevelKind;
@@ -166,7 +167,7 @@ impl Default for ServerProperties {
 }
 
 impl ServerProperties {
-    pub fn apply(&mut self, manifest: &Manifest) {
+    pub fn apply(&mut self, manifest: &Manifest, lockfile: &Lockfile) {
         self.level_name = manifest.name.clone();
         self.motd = manifest.description.clone();
         self.enable_rcon = manifest.backups.enabled;
@@ -181,6 +182,37 @@ impl ServerProperties {
         self.level_seed = manifest.server.seed.clone();
         self.view_distance = manifest.server.view_distance;
         self.simulation_distance = manifest.server.simulation_distance;
+
+        let mut datapacks: Vec<_> = manifest.datapacks.iter().collect();
+        datapacks.sort_by_key(|(name, _)| *name);
+
+        for (name, datapack) in datapacks {
+            let pack = format!("file/{}", datapack.file_name(name));
+
+            if datapack.enabled() {
+                self.initial_enabled_packs.push(pack);
+            } else {
+                self.initial_disabled_packs.push(pack);
+            }
+        }
+
+        if let Some(ref pack) = manifest.server.resource_pack
+            && let Some(ref locked) = lockfile.resource_pack
+        {
+            self.resource_pack = Some(locked.url.to_string());
+            self.resource_pack_sha1 = Some(hex::encode(locked.hash.hash()));
+            self.resource_pack_id = Some(locked.id());
+            // the prompt is a text component, a json string is the plain text one
+            self.resource_pack_prompt = pack
+                .prompt
+                .as_ref()
+                .map(|p| serde_json::Value::from(p.as_str()).to_string());
+            self.require_resource_pack = pack.required;
+        }
+    }
+
+    pub fn level_name(&self) -> &str {
+        &self.level_name
     }
 
     pub fn to_string(&self) -> McResult<String> {
@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::utils::errors::McResult;
+use crate::utils::product_descriptor::RawProductDescriptor;
+
+const MANIFEST_PATH: &str = "manifest.json";
+
+/// A CurseForge modpack, a zip archive with a manifest.json and an overrides directory.
+pub struct CurseForgePack {
+    archive: zip::ZipArchive<fs::File>,
+    pub manifest: CurseForgePackManifest
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgePackManifest {
+    pub minecraft: CurseForgePackMinecraft,
+    pub manifest_type: String,
+    pub name: String,
+
+    #[serde(default)]
+    pub version: String,
+
+    pub files: Vec<CurseForgePackFile>,
+
+    #[serde(default = "default_overrides")]
+    pub overrides: String
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgePackMinecraft {
+    pub version: String,
+
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseForgePackModLoader>
+}
+
+#[derive(Deserialize)]
+pub struct CurseForgePackModLoader {
+    pub id: String,
+
+    #[serde(default)]
+    pub primary: bool
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgePackFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u64,
+
+    #[serde(rename = "fileID")]
+    pub file_id: u64,
+
+    #[serde(default = "default_required")]
+    pub required: bool
+}
+
+fn default_overrides() -> String {
+    String::from("overrides")
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl CurseForgePack {
+    pub fn open(path: &Path) -> McResult<CurseForgePack> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("could not open modpack {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)?;
+
+        let manifest = {
+            let mut manifest_file = archive
+                .by_name(MANIFEST_PATH)
+                .context("could not find manifest.json in the modpack")?;
+            let mut manifest_string = String::new();
+            manifest_file.read_to_string(&mut manifest_string)?;
+
+            serde_json::from_str::<CurseForgePackManifest>(&manifest_string)
+                .context("could not parse manifest.json")?
+        };
+
+        if manifest.manifest_type != "minecraftModpack" {
+            anyhow::bail!("manifest.json is not a minecraft modpack");
+        }
+
+        Ok(CurseForgePack { archive, manifest })
+    }
+
+    /// The files of the overrides directory, relative to the instance.
+    pub fn overrides(&mut self) -> McResult<Vec<(PathBuf, Vec<u8>)>> {
+        let mut overrides = Vec::new();
+
+        for i in 0..self.archive.len() {
+            let mut file = self.archive.by_index(i)?;
+
+            if file.is_dir() {
+                continue;
+            }
+
+            let Some(path) = file.enclosed_name() else {
+                continue;
+            };
+
+            let Ok(path) = path.strip_prefix(&self.manifest.overrides) else {
+                continue;
+            };
+
+            let path = path.to_path_buf();
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+
+            overrides.push((path, contents));
+        }
+
+        Ok(overrides)
+    }
+}
+
+impl CurseForgePackMinecraft {
+    pub fn loader(&self) -> McResult<Option<RawProductDescriptor>> {
+        let Some(loader) = self
+            .mod_loaders
+            .iter()
+            .find(|l| l.primary)
+            .or(self.mod_loaders.first())
+        else {
+            return Ok(None);
+        };
+
+        match loader.id.split_once('-') {
+            Some(("fabric", version)) => Ok(Some(RawProductDescriptor {
+                product: String::from("fabric"),
+                version: Some(version.to_string())
+            })),
+            _ => anyhow::bail!("modpacks using {} are not supported", loader.id)
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod curseforge_pack;
 pub mod loader;
+pub mod mrpack;
+pub mod packwiz;
+pub mod resolver;
 pub mod service;
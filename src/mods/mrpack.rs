@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use url::Url;
+
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
+use crate::services::modrinth_api::ModrinthApiSideSupport;
+use crate::utils::errors::McResult;
+use crate::utils::product_descriptor::RawProductDescriptor;
+
+const INDEX_PATH: &str = "modrinth.index.json";
+
+/// Directories of a pack copied into the instance, the later ones take precedence.
+const OVERRIDES: [&str; 2] = ["overrides", "server-overrides"];
+
+/// A Modrinth modpack (https://support.modrinth.com/en/articles/8802351-modrinth-modpack-format-mrpack)
+pub struct Mrpack {
+    archive: zip::ZipArchive<fs::File>,
+    pub index: MrpackIndex
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    pub format_version: u32,
+    pub game: String,
+    pub version_id: String,
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+
+    pub files: Vec<MrpackFile>,
+    pub dependencies: HashMap<String, String>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackFile {
+    pub path: String,
+    pub hashes: MrpackFileHashes,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<MrpackFileEnv>,
+
+    pub downloads: Vec<Url>,
+    pub file_size: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MrpackFileHashes {
+    pub sha1: String,
+    pub sha512: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MrpackFileEnv {
+    pub client: ModrinthApiSideSupport,
+    pub server: ModrinthApiSideSupport
+}
+
+impl Mrpack {
+    pub fn open(path: &Path) -> McResult<Mrpack> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("could not open modpack {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)?;
+
+        let index = {
+            let mut index_file = archive
+                .by_name(INDEX_PATH)
+                .context("could not find modrinth.index.json in the modpack")?;
+            let mut index_string = String::new();
+            index_file.read_to_string(&mut index_string)?;
+
+            serde_json::from_str::<MrpackIndex>(&index_string)
+                .context("could not parse modrinth.index.json")?
+        };
+
+        if index.game != "minecraft" {
+            anyhow::bail!("modpacks for {} are not supported", index.game);
+        }
+
+        Ok(Mrpack { archive, index })
+    }
+
+    /// Writes a modpack to `output`, `overrides` maps paths relative to the instance to the files
+    /// to copy there.
+    pub fn create(
+        output: &Path,
+        index: &MrpackIndex,
+        overrides: &[(PathBuf, PathBuf)]
+    ) -> McResult<()> {
+        let file = fs::File::create(output)
+            .with_context(|| format!("could not create modpack {}", output.display()))?;
+        let mut archive = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        archive.start_file(INDEX_PATH, options)?;
+        archive.write_all(serde_json::to_string_pretty(index)?.as_bytes())?;
+
+        for (path, source) in overrides {
+            let name = Path::new(OVERRIDES[0]).join(path);
+
+            archive.start_file(name.to_string_lossy().replace('\\', "/"), options)?;
+            archive.write_all(&fs::read(source)?)?;
+        }
+
+        archive.finish()?;
+
+        Ok(())
+    }
+
+    /// The files of the overrides directories that apply to a server, relative to the instance.
+    pub fn server_overrides(&mut self) -> McResult<Vec<(PathBuf, Vec<u8>)>> {
+        let mut overrides = Vec::new();
+
+        for prefix in OVERRIDES {
+            for i in 0..self.archive.len() {
+                let mut file = self.archive.by_index(i)?;
+
+                if file.is_dir() {
+                    continue;
+                }
+
+                let Some(path) = file.enclosed_name() else {
+                    continue;
+                };
+
+                let Ok(path) = path.strip_prefix(prefix) else {
+                    continue;
+                };
+
+                let path = path.to_path_buf();
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)?;
+
+                overrides.push((path, contents));
+            }
+        }
+
+        Ok(overrides)
+    }
+}
+
+impl MrpackIndex {
+    pub fn game_version(&self) -> McResult<&String> {
+        self.dependencies
+            .get("minecraft")
+            .ok_or_else(|| anyhow::anyhow!("the modpack does not declare a minecraft version"))
+    }
+
+    pub fn loader(&self) -> McResult<Option<RawProductDescriptor>> {
+        let mut loader = None;
+
+        for (dependency, version) in &self.dependencies {
+            match dependency.as_str() {
+                "minecraft" => {}
+                "fabric-loader" => {
+                    loader = Some(RawProductDescriptor {
+                        product: String::from("fabric"),
+                        version: Some(version.clone())
+                    })
+                }
+                _ => anyhow::bail!("modpacks using {} are not supported", dependency)
+            }
+        }
+
+        Ok(loader)
+    }
+}
+
+impl MrpackFile {
+    pub fn supports_server(&self) -> bool {
+        self.env
+            .as_ref()
+            .is_none_or(|e| e.server != ModrinthApiSideSupport::Unsupported)
+    }
+
+    pub fn checksum(&self) -> McResult<LocalChecksum> {
+        LocalChecksum::from_hex(ChecksumAlgorithm::sha512, &self.hashes.sha512)
+    }
+
+    /// The path of the file relative to the instance, paths leaving the instance are rejected.
+    pub fn relative_path(&self) -> McResult<PathBuf> {
+        let path = PathBuf::from(&self.path);
+
+        if path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            anyhow::bail!("invalid path {} in modpack", self.path);
+        }
+
+        Ok(path)
+    }
+
+    /// The project and version ids of files hosted on the modrinth cdn.
+    pub fn modrinth_version(&self) -> Option<(String, String)> {
+        self.downloads.iter().find_map(|url| {
+            if url.host_str() != Some("cdn.modrinth.com") {
+                return None;
+            }
+
+            match url.path_segments()?.collect::<Vec<_>>()[..] {
+                ["data", project, "versions", version, _] => {
+                    Some((project.to_string(), version.to_string()))
+                }
+                _ => None
+            }
+        })
+    }
+}
+
+/// Whether `path`, relative to the instance, is a mod jar.
+pub fn is_mod(path: &Path) -> bool {
+    path.starts_with("mods") && path.extension().is_some_and(|e| e == "jar")
+}
@@ -0,0 +1,212 @@
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+use url::Url;
+
+use crate::crypto::checksum::LocalChecksum;
+use crate::utils::errors::McResult;
+use crate::utils::product_descriptor::RawProductDescriptor;
+
+/// A packwiz pack (https://packwiz.infra.link/reference/pack-format/), read from a directory or
+/// served over http.
+pub enum PackwizSource {
+    Local(PathBuf),
+    Remote(Url)
+}
+
+#[derive(Deserialize)]
+pub struct PackwizPack {
+    pub name: String,
+    pub index: PackwizPackIndex,
+    pub versions: PackwizPackVersions
+}
+
+#[derive(Deserialize)]
+pub struct PackwizPackIndex {
+    pub file: String
+}
+
+#[derive(Deserialize)]
+pub struct PackwizPackVersions {
+    pub minecraft: String,
+    pub fabric: Option<String>,
+    pub forge: Option<String>,
+    pub neoforge: Option<String>,
+    pub quilt: Option<String>
+}
+
+#[derive(Deserialize)]
+pub struct PackwizIndex {
+    #[serde(default)]
+    pub files: Vec<PackwizIndexFile>
+}
+
+#[derive(Deserialize)]
+pub struct PackwizIndexFile {
+    pub file: String,
+
+    #[serde(default)]
+    pub metafile: bool
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizMod {
+    pub name: String,
+    pub filename: String,
+
+    #[serde(default)]
+    pub side: PackwizSide,
+
+    pub download: PackwizDownload,
+
+    #[serde(default)]
+    pub update: PackwizUpdate
+}
+
+#[derive(Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PackwizSide {
+    #[default]
+    Both,
+    Client,
+    Server
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizDownload {
+    pub url: Option<Url>,
+    pub hash_format: String,
+    pub hash: String
+}
+
+#[derive(Deserialize, Default)]
+pub struct PackwizUpdate {
+    pub modrinth: Option<PackwizModrinthUpdate>,
+    pub curseforge: Option<PackwizCurseForgeUpdate>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizModrinthUpdate {
+    pub mod_id: String,
+    pub version: String
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackwizCurseForgeUpdate {
+    pub project_id: u64,
+    pub file_id: u64
+}
+
+impl PackwizSource {
+    /// A pack given as a directory, a pack.toml file or the url of a pack.toml file.
+    pub fn new(pack: &str) -> McResult<PackwizSource> {
+        if pack.starts_with("http://") || pack.starts_with("https://") {
+            return Ok(PackwizSource::Remote(Url::parse(pack)?));
+        }
+
+        let path = PathBuf::from(pack);
+
+        if path.is_dir() {
+            Ok(PackwizSource::Local(path.join("pack.toml")))
+        } else {
+            Ok(PackwizSource::Local(path))
+        }
+    }
+
+    /// Reads a file of the pack, `path` is relative to pack.toml.
+    pub async fn read(&self, client: &reqwest::Client, path: &str) -> McResult<Vec<u8>> {
+        match self {
+            PackwizSource::Local(pack) => {
+                let file = pack.parent().unwrap_or(Path::new(".")).join(path);
+
+                tokio::fs::read(&file)
+                    .await
+                    .with_context(|| format!("could not read {}", file.display()))
+            }
+            PackwizSource::Remote(pack) => {
+                let url = pack.join(path)?;
+
+                let bytes = client
+                    .get(url.clone())
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .context(format!("could not download {}", url))?
+                    .bytes()
+                    .await?;
+
+                Ok(bytes.to_vec())
+            }
+        }
+    }
+
+    pub async fn read_toml<T: serde::de::DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        path: &str
+    ) -> McResult<T> {
+        let bytes = self.read(client, path).await?;
+
+        toml::from_str::<T>(&String::from_utf8(bytes)?)
+            .with_context(|| format!("could not parse {}", path))
+    }
+
+    pub async fn pack(&self, client: &reqwest::Client) -> McResult<PackwizPack> {
+        let name = match self {
+            PackwizSource::Local(pack) => pack
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("pack.toml")),
+            PackwizSource::Remote(pack) => pack
+                .path_segments()
+                .and_then(|mut s| s.next_back())
+                .map(String::from)
+                .unwrap_or_else(|| String::from("pack.toml"))
+        };
+
+        self.read_toml(client, &name).await
+    }
+}
+
+impl PackwizPackVersions {
+    pub fn loader(&self) -> McResult<Option<RawProductDescriptor>> {
+        if self.forge.is_some() || self.neoforge.is_some() || self.quilt.is_some() {
+            anyhow::bail!("only fabric packwiz packs are supported");
+        }
+
+        Ok(self.fabric.as_ref().map(|version| RawProductDescriptor {
+            product: String::from("fabric"),
+            version: Some(version.clone())
+        }))
+    }
+}
+
+impl PackwizDownload {
+    /// The checksum of the download, packwiz also uses hash formats that can't be verified.
+    pub fn checksum(&self) -> Option<LocalChecksum> {
+        let algorithm = self.hash_format.parse().ok()?;
+
+        LocalChecksum::from_hex(algorithm, &self.hash).ok()
+    }
+}
+
+/// Rejects paths leaving the pack.
+pub fn relative_path(path: &str) -> McResult<PathBuf> {
+    let path = PathBuf::from(path);
+
+    if path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        anyhow::bail!("invalid path {} in pack", path.display());
+    }
+
+    Ok(path)
+}
@@ -0,0 +1,1182 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::manifest::ManifestMod;
+use crate::manifest::lock::ModLockfileEntry;
+use crate::manifest::lock::ModLockfileSource;
+use crate::mods::loader::LoaderKind;
+use crate::mods::service::ModDependencyKind;
+use crate::mods::service::ModProject;
+use crate::mods::service::ModService;
+use crate::mods::service::ModServiceKind;
+use crate::mods::service::ModServices;
+use crate::mods::service::ModSideSupport;
+use crate::mods::service::ModVersion;
+use crate::services::curseforge_api::CurseForgeApi;
+use crate::services::modrinth_api::ModrinthApi;
+use crate::utils::errors::McResult;
+
+/// Upper bound on resolution passes, each pass can only change versions that were not pinned.
+const MAX_PASSES: usize = 16;
+
+/// A version requirement placed on a project, either by the manifest or by a dependency.
+struct ModRequirement {
+    chain: Vec<String>,
+    version: Option<String>
+}
+
+/// The graph reachable from the manifest for a given set of version choices.
+#[derive(Default)]
+struct ModGraph {
+    order: Vec<String>,
+    chosen: HashMap<String, String>,
+    services: HashMap<String, ModServiceKind>,
+    edges: HashMap<String, Vec<(String, ModDependencyKind)>>,
+    chains: HashMap<String, Vec<String>>,
+    requirements: HashMap<String, Vec<ModRequirement>>,
+    incompatibilities: Vec<(String, String, Option<String>)>,
+    client_only: Vec<Vec<String>>
+}
+
+/// Resolves the mods of a manifest against their mod service, dependencies are resolved from the
+/// service of the mod requiring them. Every requirement placed on a project is collected before
+/// picking its version, the resolution is repeated until the picked versions stop changing.
+pub struct ModResolver<'a, M = ModrinthApi, C = CurseForgeApi> {
+    services: &'a ModServices<M, C>,
+    mods: &'a HashMap<String, ManifestMod>,
+    loader: LoaderKind,
+    game_version: &'a String,
+    locked: &'a [ModLockfileEntry],
+    versions: HashMap<(ModServiceKind, String), ModVersion>,
+    latest: HashMap<(ModServiceKind, String), String>,
+    projects: HashMap<(ModServiceKind, String), ModProject>,
+    skipped: Vec<String>
+}
+
+impl<'a, M: ModService, C: ModService> ModResolver<'a, M, C> {
+    pub fn new(
+        services: &'a ModServices<M, C>,
+        mods: &'a HashMap<String, ManifestMod>,
+        loader: LoaderKind,
+        game_version: &'a String,
+        locked: &'a [ModLockfileEntry]
+    ) -> ModResolver<'a, M, C> {
+        ModResolver {
+            services,
+            mods,
+            loader,
+            game_version,
+            locked,
+            versions: HashMap::new(),
+            latest: HashMap::new(),
+            projects: HashMap::new(),
+            skipped: Vec::new()
+        }
+    }
+
+    pub async fn resolve(&mut self) -> McResult<Vec<ModLockfileEntry>> {
+        let mut choices = HashMap::new();
+        let mut graph = self.walk(&choices).await?;
+        let mut passes = 1;
+
+        loop {
+            let next = self.choose(&graph);
+
+            if next == choices {
+                break;
+            }
+
+            if passes == MAX_PASSES {
+                anyhow::bail!("could not find a stable set of mod versions");
+            }
+
+            choices = next;
+            graph = self.walk(&choices).await?;
+            passes += 1;
+        }
+
+        let mut conflicts = self.version_conflicts(&graph).await?;
+        conflicts.extend(self.incompatibility_conflicts(&graph));
+        conflicts.extend(self.client_only_conflicts(&graph));
+
+        if !conflicts.is_empty() {
+            anyhow::bail!("could not resolve mods\n\n{}", conflicts.join("\n\n"));
+        }
+
+        let mut resolved_mods = Vec::new();
+
+        for (name, m) in self.mods {
+            if let Some(source) = m.direct_source() {
+                resolved_mods.push(ModLockfileEntry {
+                    name: name.clone(),
+                    version: None,
+                    version_number: None,
+                    source,
+                    hash: None,
+                    url: None,
+                    dependencies: Vec::new(),
+                    optional_dependencies: Vec::new()
+                });
+            }
+        }
+
+        for name in &graph.order {
+            let mut dependencies = Vec::new();
+            let mut optional_dependencies = Vec::new();
+
+            let edges = graph.edges.get(name).into_iter().flatten();
+
+            for (dependency, kind) in edges.filter(|(d, _)| !graph.is_client_only(d)) {
+                match kind {
+                    ModDependencyKind::Optional => optional_dependencies.push(dependency.clone()),
+                    _ => dependencies.push(dependency.clone())
+                }
+            }
+
+            dependencies.sort();
+            dependencies.dedup();
+            optional_dependencies.sort();
+            optional_dependencies.dedup();
+
+            let id = &graph.chosen[name];
+            let service = graph.services[name];
+            let version = &self.versions[&(service, id.clone())];
+
+            resolved_mods.push(ModLockfileEntry {
+                name: name.clone(),
+                version: Some(id.clone()),
+                version_number: Some(version.version_number.clone()),
+                source: ModLockfileSource::from(service),
+                hash: Some(version.file.hash.clone()),
+                url: Some(version.file.url.clone()),
+                dependencies,
+                optional_dependencies
+            });
+        }
+
+        resolved_mods.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(resolved_mods)
+    }
+
+    /// Walks the dependency graph from the manifest using `choices` for the projects already
+    /// decided, the other ones use their only pin, their locked version or the latest version.
+    async fn walk(&mut self, choices: &HashMap<String, String>) -> McResult<ModGraph> {
+        let mut graph = ModGraph::default();
+        let mut queue = VecDeque::new();
+
+        for (name, m) in self.mods {
+            if let Some(service) = m.service() {
+                queue.push_back((
+                    name.clone(),
+                    m.pinned_version().map(String::from),
+                    vec![name.clone()],
+                    service
+                ));
+            }
+        }
+
+        while let Some((name, version, chain, service)) = queue.pop_front() {
+            graph
+                .requirements
+                .entry(name.clone())
+                .or_default()
+                .push(ModRequirement {
+                    chain: chain.clone(),
+                    version: version.clone()
+                });
+
+            // already resolved, or provided by a url, a file or another service in the manifest
+            if graph.chosen.contains_key(&name)
+                || self
+                    .mods
+                    .get(&name)
+                    .is_some_and(|m| m.service() != Some(service))
+            {
+                continue;
+            }
+
+            let project = self.project(service, &name).await?;
+            let project_id = project.id.clone();
+
+            if project.server_side == ModSideSupport::Unsupported {
+                // a mod can be reached through several chains, it is reported once and from the
+                // manifest when it is listed there
+                match graph
+                    .client_only
+                    .iter_mut()
+                    .find(|c| c[c.len() - 1] == name)
+                {
+                    Some(existing) if chain.len() < existing.len() => *existing = chain,
+                    Some(_) => {}
+                    None => graph.client_only.push(chain)
+                }
+
+                continue;
+            }
+
+            let id = match choices.get(&name).or(version.as_ref()) {
+                Some(id) => id.clone(),
+                None => self.preferred_version(service, &name).await?
+            };
+
+            graph.order.push(name.clone());
+            graph.chosen.insert(name.clone(), id.clone());
+            graph.services.insert(name.clone(), service);
+            graph.chains.insert(name.clone(), chain.clone());
+
+            let dependencies: Vec<_> = self
+                .version(service, &project_id, &id)
+                .await?
+                .dependencies
+                .iter()
+                .map(|d| (d.project_id.clone(), d.version_id.clone(), d.kind))
+                .collect();
+
+            for (project_id, version_id, kind) in dependencies {
+                match kind {
+                    ModDependencyKind::Optional => {
+                        // only mods listed in the manifest can opt in to their optional
+                        // dependencies
+                        let Some(m) = self.mods.get(&name).filter(|m| m.has_optional()) else {
+                            continue;
+                        };
+
+                        let slug = self.slug(service, &project_id).await?;
+
+                        if m.includes_optional(&slug) {
+                            graph
+                                .edges
+                                .entry(name.clone())
+                                .or_default()
+                                .push((slug.clone(), kind));
+
+                            let mut chain = chain.clone();
+                            chain.push(slug.clone());
+
+                            queue.push_back((slug, version_id, chain, service));
+                        }
+                    }
+                    ModDependencyKind::Required => {
+                        let slug = self.slug(service, &project_id).await?;
+
+                        graph
+                            .edges
+                            .entry(name.clone())
+                            .or_default()
+                            .push((slug.clone(), kind));
+
+                        let mut chain = chain.clone();
+                        chain.push(slug.clone());
+
+                        queue.push_back((slug, version_id, chain, service));
+                    }
+                    ModDependencyKind::Incompatible => {
+                        let slug = self.slug(service, &project_id).await?;
+
+                        graph
+                            .incompatibilities
+                            .push((name.clone(), slug, version_id));
+                    }
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Picks a version for every project of `graph`. A project pinned to a single version uses
+    /// it, otherwise it keeps the version used during the walk.
+    fn choose(&self, graph: &ModGraph) -> HashMap<String, String> {
+        let mut choices = HashMap::new();
+
+        for name in &graph.order {
+            let pin = graph.requirements[name]
+                .iter()
+                .find_map(|r| r.version.clone());
+
+            let version = pin.unwrap_or_else(|| graph.chosen[name].clone());
+
+            choices.insert(name.clone(), version);
+        }
+
+        choices
+    }
+
+    async fn version_conflicts(&mut self, graph: &ModGraph) -> McResult<Vec<String>> {
+        let mut conflicts = Vec::new();
+
+        for name in &graph.order {
+            let requirements = &graph.requirements[name];
+
+            let mut pins: Vec<&String> = requirements
+                .iter()
+                .filter_map(|r| r.version.as_ref())
+                .collect();
+
+            pins.sort();
+            pins.dedup();
+
+            if pins.len() < 2 {
+                continue;
+            }
+
+            let mut lines = vec![format!("`{}` is required at conflicting versions", name)];
+
+            for requirement in requirements {
+                let Some(ref id) = requirement.version else {
+                    continue;
+                };
+
+                let version_number = self
+                    .version(graph.services[name], name, id)
+                    .await?
+                    .version_number
+                    .clone();
+
+                lines.push(format!(
+                    "  {} ({}) by: {}",
+                    version_number,
+                    id,
+                    format_chain(&requirement.chain)
+                ));
+            }
+
+            conflicts.push(lines.join("\n"));
+        }
+
+        Ok(conflicts)
+    }
+
+    fn incompatibility_conflicts(&self, graph: &ModGraph) -> Vec<String> {
+        let mut conflicts = Vec::new();
+
+        for (name, other, version) in &graph.incompatibilities {
+            let other_chain = match graph.chosen.get(other) {
+                Some(chosen) if version.as_ref().is_none_or(|v| v == chosen) => {
+                    graph.chains[other].clone()
+                }
+                Some(_) => continue,
+                None => match self.mods.get(other) {
+                    Some(m) if m.direct_source().is_some() && version.is_none() => {
+                        vec![other.clone()]
+                    }
+                    _ => continue
+                }
+            };
+
+            // transitive dependencies are not in mc.toml, the mods that pull them in can
+            // acknowledge the incompatibility for them
+            let name_root = &graph.chains[name][0];
+            let other_root = &other_chain[0];
+
+            let acknowledged = [name_root, other_root].into_iter().any(|root| {
+                self.mods
+                    .get(root)
+                    .is_some_and(|m| m.allows_incompatible(name) || m.allows_incompatible(other))
+            });
+
+            if acknowledged {
+                continue;
+            }
+
+            conflicts.push(format!(
+                "`{name}` is incompatible with `{other}`\n  \
+                 {name} is pulled in by: {}\n  \
+                 {other} is pulled in by: {}\n\
+                 if this combination is known to be safe, acknowledge it with \
+                 `allow_incompatible = [\"{other}\"]` on `{name_root}` in mc.toml",
+                format_chain(&graph.chains[name]),
+                format_chain(&other_chain)
+            ));
+        }
+
+        conflicts
+    }
+
+    /// Client-only mods listed in the manifest can't be installed, the ones pulled in as
+    /// dependencies are skipped.
+    fn client_only_conflicts(&mut self, graph: &ModGraph) -> Vec<String> {
+        let mut conflicts = Vec::new();
+
+        self.skipped.clear();
+
+        for chain in &graph.client_only {
+            let name = &chain[chain.len() - 1];
+
+            if chain.len() == 1 {
+                conflicts.push(format!(
+                    "`{}` only runs on the client and cannot be installed on a server, remove it \
+                     from mc.toml",
+                    name
+                ));
+            } else {
+                self.skipped.push(format!(
+                    "skipping `{}` which only runs on the client, required by: {}",
+                    name,
+                    format_chain(&chain[..chain.len() - 1])
+                ));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Warnings about the client-only dependencies left out of the last resolution.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// The version used for an unpinned project, the locked one when there is one.
+    async fn preferred_version(
+        &mut self,
+        service: ModServiceKind,
+        name: &String
+    ) -> McResult<String> {
+        let locked = self
+            .locked
+            .iter()
+            .find(|l| l.name == *name && l.source == ModLockfileSource::from(service))
+            .and_then(|l| l.version.clone());
+
+        if let Some(locked) = locked {
+            return Ok(locked);
+        }
+
+        let key = (service, name.clone());
+
+        if let Some(latest) = self.latest.get(&key) {
+            return Ok(latest.clone());
+        }
+
+        let version = self
+            .services
+            .get_latest_version(service, name, self.loader, Some(self.game_version))
+            .await?;
+
+        let id = version.id.clone();
+
+        self.latest.insert(key, id.clone());
+        self.versions.insert((service, id.clone()), version);
+
+        Ok(id)
+    }
+
+    async fn version(
+        &mut self,
+        service: ModServiceKind,
+        project: &str,
+        id: &str
+    ) -> McResult<&ModVersion> {
+        let key = (service, id.to_string());
+
+        if !self.versions.contains_key(&key) {
+            let version = self.services.get_version(service, project, id).await?;
+
+            self.versions.insert(key.clone(), version);
+        }
+
+        Ok(&self.versions[&key])
+    }
+
+    async fn slug(&mut self, service: ModServiceKind, project_id: &str) -> McResult<String> {
+        Ok(self.project(service, project_id).await?.slug.clone())
+    }
+
+    /// Fetches a project by id or slug, caching it under both.
+    async fn project(&mut self, service: ModServiceKind, id: &str) -> McResult<&ModProject> {
+        let key = (service, id.to_string());
+
+        if !self.projects.contains_key(&key) {
+            let project = self.services.get_project(service, id).await?;
+
+            self.projects
+                .insert((service, project.slug.clone()), project.clone());
+            self.projects.insert(key.clone(), project);
+        }
+
+        Ok(&self.projects[&key])
+    }
+}
+
+impl ModGraph {
+    fn is_client_only(&self, name: &str) -> bool {
+        self.client_only.iter().any(|c| c[c.len() - 1] == name)
+    }
+}
+
+fn format_chain(chain: &[String]) -> String {
+    format!("mc.toml -> {}", chain.join(" -> "))
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+    use crate::crypto::checksum::ChecksumAlgorithm;
+    use crate::crypto::hash;
+    use crate::mods::service::ModDependency;
+    use crate::mods::service::ModFile;
+
+    const GAME_VERSION: &str = "1.21.1";
+
+    /// A version of a fake project, its dependencies are `(slug, version, kind)`.
+    struct FakeVersion {
+        slug: &'static str,
+        id: &'static str,
+        dependencies: Vec<(&'static str, Option<&'static str>, ModDependencyKind)>
+    }
+
+    /// A mod service answering from memory, project ids are `id-<slug>`.
+    #[derive(Default)]
+    struct FakeService {
+        projects: Vec<(&'static str, ModSideSupport)>,
+        versions: Vec<FakeVersion>
+    }
+
+    impl FakeService {
+        fn project(mut self, slug: &'static str, server_side: ModSideSupport) -> FakeService {
+            self.projects.push((slug, server_side));
+            self
+        }
+
+        /// Adds a version of `slug`, versions are listed newest first.
+        fn version(
+            mut self,
+            slug: &'static str,
+            id: &'static str,
+            dependencies: &[(&'static str, Option<&'static str>, ModDependencyKind)]
+        ) -> FakeService {
+            if !self.projects.iter().any(|(s, _)| *s == slug) {
+                self.projects.push((slug, ModSideSupport::Required));
+            }
+
+            self.versions.push(FakeVersion {
+                slug,
+                id,
+                dependencies: dependencies.to_vec()
+            });
+            self
+        }
+
+        fn slug(project: &str) -> &str {
+            project.strip_prefix("id-").unwrap_or(project)
+        }
+
+        fn build(version: &FakeVersion) -> ModVersion {
+            let url = format!("https://example.com/{}.jar", version.id);
+
+            ModVersion {
+                id: version.id.to_string(),
+                project_id: format!("id-{}", version.slug),
+                version_number: version.id.to_string(),
+                loaders: vec![LoaderKind::Fabric.to_string()],
+                game_versions: vec![GAME_VERSION.to_string()],
+                dependencies: version
+                    .dependencies
+                    .iter()
+                    .map(|(slug, version_id, kind)| ModDependency {
+                        project_id: format!("id-{}", slug),
+                        version_id: version_id.map(String::from),
+                        kind: *kind
+                    })
+                    .collect(),
+                file: ModFile {
+                    hash: hash::hash_bytes(url.as_bytes(), ChecksumAlgorithm::sha1).unwrap(),
+                    url: Url::parse(&url).unwrap()
+                }
+            }
+        }
+    }
+
+    impl ModService for FakeService {
+        async fn get_project(&self, id: &str) -> McResult<ModProject> {
+            let slug = FakeService::slug(id);
+
+            self.projects
+                .iter()
+                .find(|(s, _)| *s == slug)
+                .map(|(slug, server_side)| ModProject {
+                    id: format!("id-{}", slug),
+                    slug: slug.to_string(),
+                    server_side: *server_side
+                })
+                .ok_or_else(|| anyhow::anyhow!("unknown project {}", id))
+        }
+
+        async fn get_version(&self, project: &str, id: &str) -> McResult<ModVersion> {
+            let slug = FakeService::slug(project);
+
+            self.versions
+                .iter()
+                .find(|v| v.slug == slug && v.id == id)
+                .map(FakeService::build)
+                .ok_or_else(|| anyhow::anyhow!("unknown version {} of {}", id, project))
+        }
+
+        async fn get_latest_version(
+            &self,
+            project: &str,
+            _loader: LoaderKind,
+            _game_version: Option<&str>
+        ) -> McResult<ModVersion> {
+            let slug = FakeService::slug(project);
+
+            self.versions
+                .iter()
+                .find(|v| v.slug == slug)
+                .map(FakeService::build)
+                .ok_or_else(|| anyhow::anyhow!("no version of {}", project))
+        }
+
+        async fn get_versions_from_hashes(
+            &self,
+            hashes: &[String],
+            algorithm: ChecksumAlgorithm
+        ) -> McResult<HashMap<String, ModVersion>> {
+            let mut found = HashMap::new();
+
+            for version in &self.versions {
+                let version = FakeService::build(version);
+                let url = version.file.url.to_string();
+                let hash = hex::encode(hash::hash_bytes(url.as_bytes(), algorithm)?.hash());
+
+                if hashes.contains(&hash) {
+                    found.insert(hash, version);
+                }
+            }
+
+            Ok(found)
+        }
+    }
+
+    fn services(modrinth: FakeService) -> ModServices<FakeService, FakeService> {
+        ModServices {
+            modrinth,
+            curseforge: FakeService::default()
+        }
+    }
+
+    /// Resolves the mods of `manifest` against `service`.
+    async fn resolve(
+        service: FakeService,
+        manifest: &str,
+        locked: &[ModLockfileEntry]
+    ) -> McResult<Vec<ModLockfileEntry>> {
+        let services = services(service);
+        let mods = manifest_mods(manifest);
+        let game_version = String::from(GAME_VERSION);
+
+        ModResolver::new(&services, &mods, LoaderKind::Fabric, &game_version, locked)
+            .resolve()
+            .await
+    }
+
+    /// The `name@version` of every resolved mod.
+    fn descriptors(mods: &[ModLockfileEntry]) -> Vec<String> {
+        mods.iter().map(|m| m.descriptor().to_string()).collect()
+    }
+
+    fn manifest_mods(s: &str) -> HashMap<String, ManifestMod> {
+        toml::from_str(s).unwrap()
+    }
+
+    fn chain(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// Adds `names[last]` to `graph` as reached through `names`, at the version `chosen`.
+    fn visit(graph: &mut ModGraph, names: &[&str], chosen: &str, pin: Option<&str>) {
+        let name = names[names.len() - 1].to_string();
+
+        if !graph.chosen.contains_key(&name) {
+            graph.order.push(name.clone());
+            graph.chosen.insert(name.clone(), chosen.to_string());
+            graph
+                .services
+                .insert(name.clone(), ModServiceKind::Modrinth);
+            graph.chains.insert(name.clone(), chain(names));
+        }
+
+        graph
+            .requirements
+            .entry(name)
+            .or_default()
+            .push(ModRequirement {
+                chain: chain(names),
+                version: pin.map(String::from)
+            });
+    }
+
+    fn with_resolver<T>(
+        mods: &str,
+        f: impl FnOnce(&mut ModResolver<FakeService, FakeService>) -> T
+    ) -> T {
+        let services = services(FakeService::default());
+        let mods = manifest_mods(mods);
+        let game_version = String::from(GAME_VERSION);
+        let mut resolver =
+            ModResolver::new(&services, &mods, LoaderKind::Fabric, &game_version, &[]);
+
+        f(&mut resolver)
+    }
+
+    #[tokio::test]
+    async fn resolve_walks_required_dependencies() {
+        let service = FakeService::default()
+            .version(
+                "sodium",
+                "sodium-2",
+                &[("fabric-api", None, ModDependencyKind::Required)]
+            )
+            .version("fabric-api", "fabric-api-2", &[])
+            .version("fabric-api", "fabric-api-1", &[]);
+
+        let mods = resolve(service, r#"sodium = "*""#, &[]).await.unwrap();
+
+        assert_eq!(
+            descriptors(&mods),
+            ["fabric-api@fabric-api-2", "sodium@sodium-2"]
+        );
+        assert_eq!(mods[1].dependencies, ["fabric-api"]);
+        assert_eq!(
+            mods[0].url.as_ref().unwrap().as_str(),
+            "https://example.com/fabric-api-2.jar"
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_keeps_locked_versions_of_unpinned_mods() {
+        let service = FakeService::default()
+            .version("sodium", "sodium-2", &[])
+            .version("sodium", "sodium-1", &[]);
+
+        let locked = resolve(
+            FakeService::default().version("sodium", "sodium-1", &[]),
+            r#"sodium = "*""#,
+            &[]
+        )
+        .await
+        .unwrap();
+
+        let mods = resolve(service, r#"sodium = "*""#, &locked).await.unwrap();
+
+        assert_eq!(descriptors(&mods), ["sodium@sodium-1"]);
+    }
+
+    #[tokio::test]
+    async fn services_look_up_versions_by_hash() {
+        let services = services(FakeService::default().version("sodium", "sodium-2", &[]));
+
+        let url = "https://example.com/sodium-2.jar";
+        let hash = hex::encode(
+            hash::hash_bytes(url.as_bytes(), ChecksumAlgorithm::sha512)
+                .unwrap()
+                .hash()
+        );
+        let unknown = "00".repeat(64);
+
+        let versions = services
+            .modrinth
+            .get_versions_from_hashes(&[hash.clone(), unknown], ChecksumAlgorithm::sha512)
+            .await
+            .unwrap();
+
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[&hash].project_id, "id-sodium");
+    }
+
+    #[test]
+    fn choose_keeps_the_walked_version_of_unpinned_mods() {
+        let mut graph = ModGraph::default();
+        visit(&mut graph, &["sodium"], "latest-id", None);
+
+        let choices = with_resolver(r#"sodium = "*""#, |r| r.choose(&graph));
+
+        assert_eq!(choices["sodium"], "latest-id");
+    }
+
+    #[test]
+    fn choose_uses_a_pin_from_a_dependency() {
+        let mut graph = ModGraph::default();
+        visit(&mut graph, &["lithium"], "lithium-id", None);
+        visit(&mut graph, &["fabric-api"], "latest-id", None);
+        visit(
+            &mut graph,
+            &["lithium", "fabric-api"],
+            "latest-id",
+            Some("pinned-id")
+        );
+
+        let choices = with_resolver(
+            r#"
+            lithium = "*"
+            fabric-api = "*"
+            "#,
+            |r| r.choose(&graph)
+        );
+
+        assert_eq!(choices["lithium"], "lithium-id");
+        assert_eq!(choices["fabric-api"], "pinned-id");
+    }
+
+    #[test]
+    fn incompatible_mods_conflict() {
+        let mut graph = ModGraph::default();
+        visit(&mut graph, &["sodium"], "sodium-id", None);
+        visit(&mut graph, &["optifabric"], "optifabric-id", None);
+        graph
+            .incompatibilities
+            .push((String::from("sodium"), String::from("optifabric"), None));
+
+        let conflicts = with_resolver(
+            r#"
+            sodium = "*"
+            optifabric = "*"
+            "#,
+            |r| r.incompatibility_conflicts(&graph)
+        );
+
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].starts_with("`sodium` is incompatible with `optifabric`"));
+        assert!(conflicts[0].contains("`allow_incompatible = [\"optifabric\"]` on `sodium`"));
+    }
+
+    #[test]
+    fn incompatibility_of_a_dependency_is_acknowledged_on_its_root() {
+        let mut graph = ModGraph::default();
+        visit(&mut graph, &["iris"], "iris-id", None);
+        visit(&mut graph, &["iris", "sodium"], "sodium-id", None);
+        visit(&mut graph, &["optifabric"], "optifabric-id", None);
+        graph
+            .incompatibilities
+            .push((String::from("sodium"), String::from("optifabric"), None));
+
+        let conflicts = with_resolver(
+            r#"
+            iris = { version = "*", allow_incompatible = ["optifabric"] }
+            optifabric = "*"
+            "#,
+            |r| r.incompatibility_conflicts(&graph)
+        );
+
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn incompatibility_with_another_version_does_not_conflict() {
+        let mut graph = ModGraph::default();
+        visit(&mut graph, &["sodium"], "sodium-id", None);
+        visit(&mut graph, &["optifabric"], "optifabric-id", None);
+        graph.incompatibilities.push((
+            String::from("sodium"),
+            String::from("optifabric"),
+            Some(String::from("old-optifabric-id"))
+        ));
+
+        let conflicts = with_resolver(
+            r#"
+            sodium = "*"
+            optifabric = "*"
+            "#,
+            |r| r.incompatibility_conflicts(&graph)
+        );
+
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn client_only_mods_conflict_from_the_manifest_and_are_skipped_as_dependencies() {
+        let mut graph = ModGraph::default();
+        graph.client_only.push(chain(&["modmenu"]));
+        graph.client_only.push(chain(&["iris", "sodium-extra"]));
+
+        with_resolver(
+            r#"
+            modmenu = "*"
+            iris = "*"
+            "#,
+            |r| {
+                let conflicts = r.client_only_conflicts(&graph);
+
+                assert_eq!(conflicts.len(), 1);
+                assert!(conflicts[0].starts_with("`modmenu` only runs on the client"));
+                assert_eq!(r.skipped().len(), 1);
+                assert!(r.skipped()[0].contains("`sodium-extra`"));
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_uses_the_version_a_dependency_pins() {
+        let service = FakeService::default()
+            .version(
+                "sodium",
+                "sodium-2",
+                &[(
+                    "fabric-api",
+                    Some("fabric-api-1"),
+                    ModDependencyKind::Required
+                )]
+            )
+            .version("fabric-api", "fabric-api-2", &[])
+            .version(
+                "fabric-api",
+                "fabric-api-1",
+                &[("indium", None, ModDependencyKind::Required)]
+            )
+            .version("indium", "indium-1", &[]);
+
+        let mods = resolve(service, r#"sodium = "*""#, &[]).await.unwrap();
+
+        // the pinned version is walked again and brings its own dependencies
+        assert_eq!(
+            descriptors(&mods),
+            [
+                "fabric-api@fabric-api-1",
+                "indium@indium-1",
+                "sodium@sodium-2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_uses_the_version_the_manifest_pins() {
+        let service = FakeService::default()
+            .version(
+                "sodium",
+                "sodium-2",
+                &[("fabric-api", None, ModDependencyKind::Required)]
+            )
+            .version("fabric-api", "fabric-api-2", &[])
+            .version("fabric-api", "fabric-api-1", &[]);
+
+        let mods = resolve(
+            service,
+            r#"
+            sodium = "*"
+            fabric-api = "fabric-api-1"
+            "#,
+            &[]
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            descriptors(&mods),
+            ["fabric-api@fabric-api-1", "sodium@sodium-2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_fails_on_conflicting_pins() {
+        let service = FakeService::default()
+            .version(
+                "sodium",
+                "sodium-2",
+                &[(
+                    "fabric-api",
+                    Some("fabric-api-1"),
+                    ModDependencyKind::Required
+                )]
+            )
+            .version(
+                "lithium",
+                "lithium-2",
+                &[(
+                    "fabric-api",
+                    Some("fabric-api-2"),
+                    ModDependencyKind::Required
+                )]
+            )
+            .version("fabric-api", "fabric-api-2", &[])
+            .version("fabric-api", "fabric-api-1", &[]);
+
+        let error = resolve(
+            service,
+            r#"
+            sodium = "*"
+            lithium = "*"
+            "#,
+            &[]
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(error.contains("`fabric-api` is required at conflicting versions"));
+        assert!(error.contains("fabric-api-1 (fabric-api-1) by: mc.toml -> sodium -> fabric-api"));
+        assert!(error.contains("fabric-api-2 (fabric-api-2) by: mc.toml -> lithium -> fabric-api"));
+    }
+
+    fn incompatible_service() -> FakeService {
+        FakeService::default()
+            .version(
+                "iris",
+                "iris-1",
+                &[("sodium", None, ModDependencyKind::Required)]
+            )
+            .version(
+                "sodium",
+                "sodium-2",
+                &[("optifabric", None, ModDependencyKind::Incompatible)]
+            )
+            .version("optifabric", "optifabric-1", &[])
+    }
+
+    #[tokio::test]
+    async fn resolve_fails_on_incompatible_mods() {
+        let error = resolve(
+            incompatible_service(),
+            r#"
+            iris = "*"
+            optifabric = "*"
+            "#,
+            &[]
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(error.contains("`sodium` is incompatible with `optifabric`"));
+        assert!(error.contains("sodium is pulled in by: mc.toml -> iris -> sodium"));
+        assert!(error.contains("`allow_incompatible = [\"optifabric\"]` on `iris`"));
+    }
+
+    #[tokio::test]
+    async fn resolve_accepts_acknowledged_incompatibilities() {
+        let mods = resolve(
+            incompatible_service(),
+            r#"
+            iris = { version = "*", allow_incompatible = ["optifabric"] }
+            optifabric = "*"
+            "#,
+            &[]
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            descriptors(&mods),
+            ["iris@iris-1", "optifabric@optifabric-1", "sodium@sodium-2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_ignores_incompatible_mods_that_are_not_installed() {
+        let mods = resolve(incompatible_service(), r#"iris = "*""#, &[])
+            .await
+            .unwrap();
+
+        assert_eq!(descriptors(&mods), ["iris@iris-1", "sodium@sodium-2"]);
+    }
+
+    fn optional_service() -> FakeService {
+        FakeService::default()
+            .version(
+                "sodium",
+                "sodium-2",
+                &[
+                    ("indium", None, ModDependencyKind::Optional),
+                    ("reeses-sodium-options", None, ModDependencyKind::Optional)
+                ]
+            )
+            .version("indium", "indium-1", &[])
+            .version("reeses-sodium-options", "reeses-sodium-options-1", &[])
+    }
+
+    #[tokio::test]
+    async fn resolve_skips_optional_dependencies_by_default() {
+        let mods = resolve(optional_service(), r#"sodium = "*""#, &[])
+            .await
+            .unwrap();
+
+        assert_eq!(descriptors(&mods), ["sodium@sodium-2"]);
+    }
+
+    #[tokio::test]
+    async fn resolve_installs_selected_optional_dependencies() {
+        let mods = resolve(
+            optional_service(),
+            r#"sodium = { version = "*", optional = ["indium"] }"#,
+            &[]
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(descriptors(&mods), ["indium@indium-1", "sodium@sodium-2"]);
+        assert!(mods[1].dependencies.is_empty());
+        assert_eq!(mods[1].optional_dependencies, ["indium"]);
+    }
+
+    #[tokio::test]
+    async fn resolve_installs_every_optional_dependency() {
+        let mods = resolve(
+            optional_service(),
+            r#"sodium = { version = "*", optional = true }"#,
+            &[]
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            descriptors(&mods),
+            [
+                "indium@indium-1",
+                "reeses-sodium-options@reeses-sodium-options-1",
+                "sodium@sodium-2"
+            ]
+        );
+    }
+
+    fn client_only_service() -> FakeService {
+        FakeService::default()
+            .project("modmenu", ModSideSupport::Unsupported)
+            .version(
+                "iris",
+                "iris-1",
+                &[("modmenu", None, ModDependencyKind::Required)]
+            )
+            .version("modmenu", "modmenu-1", &[])
+    }
+
+    #[tokio::test]
+    async fn resolve_skips_client_only_dependencies() {
+        let services = services(client_only_service());
+        let mods = manifest_mods(r#"iris = "*""#);
+        let game_version = String::from(GAME_VERSION);
+        let mut resolver =
+            ModResolver::new(&services, &mods, LoaderKind::Fabric, &game_version, &[]);
+
+        let resolved = resolver.resolve().await.unwrap();
+
+        assert_eq!(descriptors(&resolved), ["iris@iris-1"]);
+        assert!(resolved[0].dependencies.is_empty());
+        assert_eq!(
+            resolver.skipped(),
+            ["skipping `modmenu` which only runs on the client, required by: mc.toml -> iris"]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_rejects_client_only_mods_once() {
+        let error = resolve(
+            client_only_service(),
+            r#"
+            iris = "*"
+            modmenu = "*"
+            "#,
+            &[]
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert_eq!(
+            error,
+            "could not resolve mods\n\n`modmenu` only runs on the client and cannot be \
+             installed on a server, remove it from mc.toml"
+        );
+    }
+}
@@ -1,11 +1,180 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
+use anyhow::Context;
 use serde::Deserialize;
+use serde::Deserializer;
+use url::Url;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
+use crate::mods::loader::LoaderKind;
+use crate::services::curseforge_api::CurseForgeApi;
+use crate::services::modrinth_api::ModrinthApi;
+use crate::utils::errors::McResult;
+
+/// A repository of mods, mods are resolved and installed through the service configured for
+/// them in the manifest.
+pub trait ModService {
+    /// Fetches a project by id or slug.
+    async fn get_project(&self, id: &str) -> McResult<ModProject>;
+
+    /// Fetches the version `id` of `project`.
+    async fn get_version(&self, project: &str, id: &str) -> McResult<ModVersion>;
+
+    /// The newest version of `project` for `loader`, restricted to `game_version` when given.
+    async fn get_latest_version(
+        &self,
+        project: &str,
+        loader: LoaderKind,
+        game_version: Option<&str>
+    ) -> McResult<ModVersion>;
+
+    /// Finds the versions owning the files with the given hex `hashes`, keyed by hash. Files
+    /// the service does not know are left out.
+    async fn get_versions_from_hashes(
+        &self,
+        hashes: &[String],
+        algorithm: ChecksumAlgorithm
+    ) -> McResult<HashMap<String, ModVersion>>;
+}
+
+/// The services a manifest can resolve mods from, each call goes to the service of the mod.
+pub struct ModServices<M = ModrinthApi, C = CurseForgeApi> {
+    pub modrinth: M,
+    pub curseforge: C
+}
+
+impl ModServices {
+    pub fn new(client: &reqwest::Client) -> ModServices {
+        ModServices {
+            modrinth: ModrinthApi::new(client.clone()),
+            curseforge: CurseForgeApi::new(client.clone())
+        }
+    }
+}
+
+impl<M: ModService, C: ModService> ModServices<M, C> {
+    pub async fn get_project(&self, service: ModServiceKind, id: &str) -> McResult<ModProject> {
+        match service {
+            ModServiceKind::Modrinth => self.modrinth.get_project(id).await,
+            ModServiceKind::CurseForge => self.curseforge.get_project(id).await
+        }
+    }
+
+    pub async fn get_version(
+        &self,
+        service: ModServiceKind,
+        project: &str,
+        id: &str
+    ) -> McResult<ModVersion> {
+        match service {
+            ModServiceKind::Modrinth => self.modrinth.get_version(project, id).await,
+            ModServiceKind::CurseForge => self.curseforge.get_version(project, id).await
+        }
+    }
+
+    pub async fn get_latest_version(
+        &self,
+        service: ModServiceKind,
+        project: &str,
+        loader: LoaderKind,
+        game_version: Option<&str>
+    ) -> McResult<ModVersion> {
+        match service {
+            ModServiceKind::Modrinth => {
+                self.modrinth
+                    .get_latest_version(project, loader, game_version)
+                    .await
+            }
+            ModServiceKind::CurseForge => {
+                self.curseforge
+                    .get_latest_version(project, loader, game_version)
+                    .await
+            }
+        }
+    }
+
+    pub async fn get_versions_from_hashes(
+        &self,
+        service: ModServiceKind,
+        hashes: &[String],
+        algorithm: ChecksumAlgorithm
+    ) -> McResult<HashMap<String, ModVersion>> {
+        match service {
+            ModServiceKind::Modrinth => {
+                self.modrinth
+                    .get_versions_from_hashes(hashes, algorithm)
+                    .await
+            }
+            ModServiceKind::CurseForge => {
+                self.curseforge
+                    .get_versions_from_hashes(hashes, algorithm)
+                    .await
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ModProject {
+    pub id: String,
+    pub slug: String,
+    pub server_side: ModSideSupport
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModSideSupport {
+    Required,
+    Optional,
+    Unsupported,
+    Unknown
+}
+
+pub struct ModVersion {
+    pub id: String,
+    pub project_id: String,
+    pub version_number: String,
+    pub loaders: Vec<String>,
+    pub game_versions: Vec<String>,
+    pub dependencies: Vec<ModDependency>,
+    pub file: ModFile
+}
+
+impl ModVersion {
+    /// Whether this version is published for `loader` on `game_version`.
+    pub fn supports(&self, loader: LoaderKind, game_version: &str) -> bool {
+        self.loaders.iter().any(|l| *l == loader.to_string())
+            && self.game_versions.iter().any(|v| v == game_version)
+    }
+}
+
+pub struct ModDependency {
+    pub project_id: String,
+    pub version_id: Option<String>,
+    pub kind: ModDependencyKind
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ModDependencyKind {
+    Required,
+    Incompatible,
+    Optional
+}
+
+/// The jar installed for a version.
+pub struct ModFile {
+    pub url: Url,
+    pub hash: LocalChecksum
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum ModServiceKind {
-    Modrinth
+    Modrinth,
+    /// CurseForge does not tell which side a mod runs on, client-only mods from it can't be
+    /// detected and are installed on the server.
+    CurseForge
 }
 
 impl Default for ModServiceKind {
@@ -14,13 +183,27 @@ impl Default for ModServiceKind {
     }
 }
 
+impl<'de> Deserialize<'de> for ModServiceKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+
+        ModServiceKind::from_str(&s)
+            .with_context(|| format!("could not parse mod service: {s}"))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 impl FromStr for ModServiceKind {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "modrinth" => Ok(ModServiceKind::Modrinth),
-            _ => anyhow::bail!("mod service must be modrinth")
+            "curseforge" => Ok(ModServiceKind::CurseForge),
+            _ => anyhow::bail!("mod service must be modrinth or curseforge")
         }
     }
 }
@@ -28,7 +211,8 @@ impl FromStr for ModServiceKind {
 impl fmt::Display for ModServiceKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
-            ModServiceKind::Modrinth => "modrinth"
+            ModServiceKind::Modrinth => "modrinth",
+            ModServiceKind::CurseForge => "curseforge"
         };
 
         write!(f, "{}", s)
@@ -15,7 +15,7 @@ pub async fn deflate_tar_gz<R: AsyncRead + Unpin>(
     mut reader: Hasher<R>,
     checksum: Option<LocalChecksum>,
     output: &Path
-) -> McResult<()> {
+) -> McResult<LocalChecksum> {
     let dir = tempfile::tempdir()?;
 
     let buf = tokio::io::BufReader::with_capacity(256 * 1024, &mut reader);
@@ -30,8 +30,10 @@ pub async fn deflate_tar_gz<R: AsyncRead + Unpin>(
 
     tar.unpack(dir.path()).await?;
 
+    let hash = reader.checksum()?;
+
     if let Some(checksum) = checksum {
-        if reader.hash().as_ref() != checksum.hash() {
+        if hash != checksum {
             anyhow::bail!("checksum does not match")
         }
     }
@@ -57,14 +59,14 @@ pub async fn deflate_tar_gz<R: AsyncRead + Unpin>(
 
     tokio::fs::rename(source, output).await?;
 
-    Ok(())
+    Ok(hash)
 }
 
 pub async fn deflate_zip<R: AsyncRead + Unpin>(
     mut reader: Hasher<R>,
     checksum: Option<LocalChecksum>,
     output: &Path
-) -> McResult<()> {
+) -> McResult<LocalChecksum> {
     let dir = tempfile::tempdir()?;
     let archive_path = dir.path().join("archive.zip.partial");
     let async_file = tokio::fs::File::create(&archive_path).await?;
@@ -74,8 +76,10 @@ pub async fn deflate_zip<R: AsyncRead + Unpin>(
     tokio::io::copy(&mut reader, &mut writer).await?;
     writer.flush().await?;
 
+    let hash = reader.checksum()?;
+
     if let Some(checksum) = checksum {
-        if reader.hash().as_ref() != checksum.hash() {
+        if hash != checksum {
             anyhow::bail!("checksum does not match")
         }
     }
@@ -85,5 +89,5 @@ pub async fn deflate_zip<R: AsyncRead + Unpin>(
     let mut archive = zip::ZipArchive::new(file)?;
     archive.extract_unwrapped_root_dir(output, zip::read::root_dir_common_filter)?;
 
-    Ok(())
+    Ok(hash)
 }
@@ -1,12 +1,12 @@
 use url::Url;
 
-use crate::crypto::checksum::ChecksumAlgorithm;
 use crate::crypto::checksum::ChecksumRef;
 use crate::crypto::checksum::LocalChecksum;
 use crate::utils::errors::McResult;
 
 pub enum ArtifactKind {
     Jar,
+    File,
     Zip,
     TarGz
 }
@@ -31,23 +31,7 @@ impl ArtifactSource {
                         .text()
                         .await?;
 
-                    match remote.algorithm {
-                        ChecksumAlgorithm::md5 => {
-                            let mut digest = [0u8; 16];
-                            hex::decode_to_slice(data, &mut digest)?;
-                            Ok(Some(LocalChecksum::md5(digest)))
-                        }
-                        ChecksumAlgorithm::sha1 => {
-                            let mut digest = [0u8; 20];
-                            hex::decode_to_slice(data, &mut digest)?;
-                            Ok(Some(LocalChecksum::sha1(digest)))
-                        }
-                        ChecksumAlgorithm::sha256 => {
-                            let mut digest = [0u8; 32];
-                            hex::decode_to_slice(data, &mut digest)?;
-                            Ok(Some(LocalChecksum::sha256(digest)))
-                        }
-                    }
+                    Ok(Some(LocalChecksum::from_hex(remote.algorithm, &data)?))
                 }
             }
         } else {
@@ -10,6 +10,7 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::StreamReader;
 use tracing::debug;
+use url::Url;
 
 use crate::crypto::checksum::ChecksumAlgorithm;
 use crate::crypto::checksum::LocalChecksum;
@@ -24,7 +25,7 @@ pub async fn stream_artifact(
     client: &reqwest::Client,
     source: ArtifactSource,
     output: &Path
-) -> McResult<()> {
+) -> McResult<LocalChecksum> {
     debug!("downloading from: {}", source.url);
 
     let checksum = source
@@ -39,35 +40,45 @@ pub async fn stream_artifact(
         .map(|s| s.map_err(|e| io::Error::new(io::ErrorKind::Other, e)));
     let reader = StreamReader::new(stream);
 
-    // TODO: clean this up to avoid hashing files when checksum is None.
+    // artifacts without a known checksum are still hashed so the caller can record it.
     let hasher = Hasher::new(
         reader,
         checksum
-            .clone()
+            .as_ref()
             .map(|c| c.algorithm())
-            .unwrap_or(ChecksumAlgorithm::md5)
+            .unwrap_or(ChecksumAlgorithm::sha256)
     );
 
     match source.kind {
-        ArtifactKind::Zip => {
-            deflate_zip(hasher, checksum, output).await?;
-        }
-        ArtifactKind::TarGz => {
-            deflate_tar_gz(hasher, checksum, output).await?;
-        }
-        _ => {
-            save_file(hasher, checksum, output).await?;
-        }
-    };
+        ArtifactKind::Zip => deflate_zip(hasher, checksum, output).await,
+        ArtifactKind::TarGz => deflate_tar_gz(hasher, checksum, output).await,
+        _ => save_file(hasher, checksum, output).await
+    }
+}
 
-    Ok(())
+/// Hashes the file at `url` without keeping it.
+pub async fn hash_remote(
+    client: &reqwest::Client,
+    url: Url,
+    algorithm: ChecksumAlgorithm
+) -> McResult<LocalChecksum> {
+    debug!("hashing: {}", url);
+
+    let r = client.get(url).send().await?.error_for_status()?;
+
+    let stream = r.bytes_stream().map(|s| s.map_err(io::Error::other));
+    let mut hasher = Hasher::new(StreamReader::new(stream), algorithm);
+
+    tokio::io::copy(&mut hasher, &mut tokio::io::sink()).await?;
+
+    hasher.checksum()
 }
 
 pub async fn save_file<R: AsyncRead + Unpin>(
     mut reader: Hasher<R>,
     checksum: Option<LocalChecksum>,
     output: &Path
-) -> McResult<()> {
+) -> McResult<LocalChecksum> {
     let dir = tempfile::tempdir()?;
     let file_path = dir.path().join("file.partial");
     let async_file = tokio::fs::File::create(&file_path).await?;
@@ -77,13 +88,15 @@ pub async fn save_file<R: AsyncRead + Unpin>(
     tokio::io::copy(&mut reader, &mut writer).await?;
     writer.flush().await?;
 
+    let hash = reader.checksum()?;
+
     if let Some(checksum) = checksum {
-        if reader.hash().as_ref() != checksum.hash() {
+        if hash != checksum {
             anyhow::bail!("checksum does not match")
         }
     }
 
     tokio::fs::rename(file_path, output).await?;
 
-    Ok(())
+    Ok(hash)
 }
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::context::McContext;
+use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
+use crate::minecraft::gamerules;
+use crate::utils::errors::McResult;
+
+pub struct CheckOptions {
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+/// Validates mc.toml against the locked minecraft version without changing anything.
+pub async fn check(context: &mut McContext, options: &CheckOptions) -> McResult<()> {
+    let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
+        .await
+        .context("could not find mc.toml file")?;
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let minecraft_version = manifest
+        .minecraft
+        .locked_version(context, &lockfile)
+        .await?;
+
+    let mut errors = 0;
+
+    if let Some(ref pack) = manifest.server.resource_pack
+        && let Err(e) = pack.project()
+    {
+        _ = context.shell().error(e);
+        errors += 1;
+    }
+
+    errors += check_gamerules(context, &manifest, &minecraft_version);
+
+    if errors > 0 {
+        anyhow::bail!("found {} problem(s) in mc.toml", errors);
+    }
+
+    _ = context.shell().status("Finished", "mc.toml is valid");
+
+    Ok(())
+}
+
+/// Reports the gamerules that don't exist in `version` or have the wrong type, returns how many
+/// were rejected.
+pub fn check_gamerules(context: &mut McContext, manifest: &Manifest, version: &str) -> usize {
+    if manifest.gamerules.is_empty() {
+        return 0;
+    }
+
+    // an unchecked typo would only show up in the server console once it is running
+    if !gamerules::is_known_version(version) {
+        _ = context.shell().error(format!(
+            "could not verify the gamerules of minecraft {}, only releases up to {} are known",
+            version,
+            gamerules::last_known_version()
+        ));
+
+        return 1;
+    }
+
+    // mods can register their own gamerules
+    let modded = manifest.minecraft.loader.is_some();
+
+    let mut names: Vec<&String> = manifest.gamerules.keys().collect();
+    names.sort();
+
+    let mut errors = 0;
+
+    for name in names {
+        let value = &manifest.gamerules[name];
+
+        match gamerules::kind(version, name) {
+            Some(kind) if kind != value.kind() => {
+                _ = context.shell().error(format!(
+                    "the gamerule `{}` expects {}, found {}",
+                    name, kind, value
+                ));
+                errors += 1;
+            }
+            Some(_) => {}
+            None if modded => {
+                _ = context.shell().warn(format!(
+                    "`{}` is not a gamerule of minecraft {}, make sure a mod adds it",
+                    name, version
+                ));
+            }
+            None => {
+                _ = context.shell().error(format!(
+                    "unknown gamerule `{}` for minecraft {}",
+                    name, version
+                ));
+                errors += 1;
+            }
+        }
+    }
+
+    errors
+}
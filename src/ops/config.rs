@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
+use crate::crypto::hash;
+use crate::manifest::Manifest;
+use crate::utils::errors::McResult;
+use crate::utils::fs::walk;
+
+/// The directory of the project copied over the config directory of the instance.
+pub const CONFIG_DIRECTORY: &str = "config";
+
+/// Files with this extension have their variables substituted, the extension is dropped in the
+/// instance. Other files are copied as is.
+pub const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// The hashes of the config files written in the instance, used to notice when the server
+/// rewrites one of them.
+pub const CONFIG_STATE_FILE: &str = ".mc-config.toml";
+
+pub struct SyncConfigOptions {
+    pub source_path: PathBuf,
+    pub config_path: PathBuf,
+    pub state_path: PathBuf,
+    pub variables: HashMap<String, String>
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq)]
+struct ConfigState {
+    #[serde(default)]
+    files: BTreeMap<String, LocalChecksum>
+}
+
+/// The variables available in config templates as `${name}`, environment variables are available
+/// as `${env.NAME}`.
+pub fn variables(manifest: &Manifest, minecraft_version: &str) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+
+    variables.insert(String::from("name"), manifest.name.clone());
+    variables.insert(String::from("description"), manifest.description.clone());
+    variables.insert(
+        String::from("minecraft.version"),
+        minecraft_version.to_string()
+    );
+    variables.insert(
+        String::from("server.port"),
+        manifest.server.port.to_string()
+    );
+    variables.insert(
+        String::from("server.rcon_port"),
+        manifest.server.rcon_port.to_string()
+    );
+    variables.insert(
+        String::from("server.capacity"),
+        manifest.server.capacity.to_string()
+    );
+    variables.insert(
+        String::from("server.view_distance"),
+        manifest.server.view_distance.to_string()
+    );
+    variables.insert(
+        String::from("server.simulation_distance"),
+        manifest.server.simulation_distance.to_string()
+    );
+
+    if let Some(ref ip) = manifest.server.ip {
+        variables.insert(String::from("server.ip"), ip.clone());
+    }
+
+    variables
+}
+
+/// Copies the config directory of the project into the instance, substituting variables in
+/// templates. Managed files the server changed since the last sync are reported before being
+/// overwritten.
+pub async fn sync(context: &mut McContext, options: &SyncConfigOptions) -> McResult<()> {
+    let state = read_state(&options.state_path).await?;
+
+    if !options.source_path.is_dir() && state.files.is_empty() {
+        return Ok(());
+    }
+
+    let sources = if options.source_path.is_dir() {
+        walk(&options.source_path)?
+    } else {
+        Vec::new()
+    };
+
+    let mut files = BTreeMap::new();
+
+    for source in sources {
+        let mut relative = source.strip_prefix(&options.source_path)?.to_path_buf();
+        let contents = tokio::fs::read(&source).await?;
+
+        let contents = if relative
+            .extension()
+            .is_some_and(|e| e == TEMPLATE_EXTENSION)
+        {
+            let contents = render(contents, &options.variables).with_context(|| {
+                format!(
+                    "could not render {}/{}",
+                    CONFIG_DIRECTORY,
+                    relative.display()
+                )
+            })?;
+            relative.set_extension("");
+
+            contents
+        } else {
+            contents
+        };
+
+        let key = relative.to_string_lossy().replace('\\', "/");
+
+        if files.contains_key(&key) {
+            anyhow::bail!(
+                "{}/{} is both a template and a plain file",
+                CONFIG_DIRECTORY,
+                key
+            );
+        }
+
+        let hash = hash::hash_bytes(&contents, ChecksumAlgorithm::sha256)?;
+
+        let output = options.config_path.join(&relative);
+        let current = if output.is_file() {
+            Some(hash::hash_file(&output, ChecksumAlgorithm::sha256).await?)
+        } else {
+            None
+        };
+
+        if let Some(ref current) = current
+            && let Some(written) = state.files.get(&key)
+            && current != written
+            && *current != hash
+        {
+            _ = context.shell().warn(format!(
+                "{}/{} was changed in the instance, overwriting it with the project version",
+                CONFIG_DIRECTORY, key
+            ));
+        }
+
+        if current.as_ref() != Some(&hash) {
+            if let Some(parent) = output.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            tokio::fs::write(&output, &contents).await?;
+
+            _ = context
+                .shell()
+                .status("Writing", format!("{}/{}", CONFIG_DIRECTORY, key));
+        }
+
+        files.insert(key, hash);
+    }
+
+    for (key, written) in &state.files {
+        if files.contains_key(key) || !is_relative(key) {
+            continue;
+        }
+
+        let output = options.config_path.join(key);
+
+        if !output.is_file() {
+            continue;
+        }
+
+        if hash::hash_file(&output, ChecksumAlgorithm::sha256).await? == *written {
+            tokio::fs::remove_file(&output).await?;
+
+            _ = context
+                .shell()
+                .status("Removing", format!("{}/{}", CONFIG_DIRECTORY, key));
+        } else {
+            _ = context.shell().warn(format!(
+                "{}/{} is no longer in the project but was changed in the instance, keeping it",
+                CONFIG_DIRECTORY, key
+            ));
+        }
+    }
+
+    let state_updated = ConfigState { files };
+
+    if state_updated != state {
+        tokio::fs::write(&options.state_path, toml::to_string(&state_updated)?).await?;
+    }
+
+    Ok(())
+}
+
+/// Warns about the managed files the server rewrote, they are overwritten on the next sync.
+pub async fn report_drift(context: &mut McContext, options: &SyncConfigOptions) -> McResult<()> {
+    let state = read_state(&options.state_path).await?;
+
+    for (key, written) in &state.files {
+        let output = options.config_path.join(key);
+
+        if !is_relative(key) || !output.is_file() {
+            continue;
+        }
+
+        if hash::hash_file(&output, ChecksumAlgorithm::sha256).await? != *written {
+            _ = context.shell().warn(format!(
+                "the server rewrote {}/{}, copy the changes to the project or they will be \
+                 overwritten on the next run",
+                CONFIG_DIRECTORY, key
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces `${variable}` in a template, `$${` is kept as a literal `${`.
+fn render(contents: Vec<u8>, variables: &HashMap<String, String>) -> McResult<Vec<u8>> {
+    let text = String::from_utf8(contents).context("templates must be utf-8 text")?;
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // the escaping `$` stands for the literal one
+            output.push_str(&rest[..start]);
+            output.push('{');
+            rest = &rest[start + 2..];
+
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+
+        let Some(end) = rest[start..].find('}') else {
+            let line = rest[start..].lines().next().unwrap_or_default();
+
+            anyhow::bail!("unterminated variable `{}`", line);
+        };
+
+        let name = rest[start + 2..start + end].trim();
+
+        let value = match name.strip_prefix("env.") {
+            Some(variable) => std::env::var(variable).with_context(|| {
+                format!("could not find the environment variable `{}`", variable)
+            })?,
+            None => variables
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("unknown variable `{}`", name))?
+        };
+
+        output.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+
+    output.push_str(rest);
+
+    Ok(output.into_bytes())
+}
+
+async fn read_state(path: &Path) -> McResult<ConfigState> {
+    if !path.exists() {
+        return Ok(ConfigState::default());
+    }
+
+    let s = tokio::fs::read_to_string(path).await?;
+
+    toml::from_str::<ConfigState>(&s).with_context(|| format!("could not parse {}", path.display()))
+}
+
+/// Whether `key` stays inside the config directory.
+fn is_relative(key: &str) -> bool {
+    Path::new(key)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_str(s: &str) -> McResult<String> {
+        let variables = HashMap::from([
+            (String::from("name"), String::from("survival")),
+            (String::from("server.port"), String::from("25565"))
+        ]);
+
+        Ok(String::from_utf8(render(s.as_bytes().to_vec(), &variables)?).unwrap())
+    }
+
+    #[test]
+    fn render_substitutes_variables() {
+        assert_eq!(
+            render_str("motd=${name} on ${ server.port }").unwrap(),
+            "motd=survival on 25565"
+        );
+    }
+
+    #[test]
+    fn render_keeps_escaped_variables() {
+        assert_eq!(
+            render_str("a=$${name} b=${name} c=$$").unwrap(),
+            "a=${name} b=survival c=$$"
+        );
+    }
+
+    #[test]
+    fn render_reads_environment_variables() {
+        let path = std::env::var("PATH").unwrap();
+
+        assert_eq!(
+            render_str("path=${env.PATH}").unwrap(),
+            format!("path={}", path)
+        );
+    }
+
+    #[test]
+    fn render_rejects_unknown_and_unterminated_variables() {
+        let unknown = render_str("${missing}").unwrap_err();
+        assert_eq!(unknown.to_string(), "unknown variable `missing`");
+
+        let unterminated = render_str("a=${name\nb=c").unwrap_err();
+        assert_eq!(unterminated.to_string(), "unterminated variable `${name`");
+    }
+
+    #[test]
+    fn render_rejects_binary_templates() {
+        assert!(render(vec![0xff, 0xfe], &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn relative_keys_stay_in_the_config_directory() {
+        assert!(is_relative("mod/settings.json"));
+        assert!(!is_relative("../server.properties"));
+        assert!(!is_relative("/etc/passwd"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::ChecksumRef;
+use crate::crypto::hash;
+use crate::manifest::ManifestDatapack;
+use crate::manifest::lock::DatapackLockfileEntry;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileSource;
+use crate::network;
+use crate::network::artifact::ArtifactKind;
+use crate::network::artifact::ArtifactSource;
+use crate::services;
+use crate::utils::errors::McResult;
+
+pub struct SyncDatapacksOptions {
+    pub game_version: String,
+    pub datapacks_path: PathBuf
+}
+
+/// Installs the datapacks of the manifest in the datapacks folder of the world. Datapacks removed
+/// from the manifest are deleted, the ones added by hand are left alone.
+pub async fn sync(
+    context: &mut McContext,
+    options: &SyncDatapacksOptions,
+    datapacks: &HashMap<String, ManifestDatapack>,
+    lockfile: &mut Lockfile
+) -> McResult<()> {
+    if datapacks.is_empty() && lockfile.datapacks.is_empty() {
+        return Ok(());
+    }
+
+    tokio::fs::create_dir_all(&options.datapacks_path).await?;
+
+    let mut entries = resolve(
+        context,
+        &options.game_version,
+        datapacks,
+        &lockfile.datapacks
+    )
+    .await?;
+
+    for entry in &mut entries {
+        let output = options.datapacks_path.join(entry.file_name());
+
+        install(context, entry, &output).await?;
+    }
+
+    for old in &lockfile.datapacks {
+        // still installed under the same name
+        if entries
+            .iter()
+            .any(|e| e.name == old.name && e.file_name() == old.file_name())
+        {
+            continue;
+        }
+
+        let path = options.datapacks_path.join(old.file_name());
+
+        if path.is_dir() {
+            tokio::fs::remove_dir_all(&path).await?;
+        } else if path.exists() {
+            tokio::fs::remove_file(&path).await?;
+        } else {
+            continue;
+        }
+
+        _ = context.shell().status("Removing", old.file_name());
+    }
+
+    lockfile.datapacks = entries;
+
+    Ok(())
+}
+
+/// Locks the datapacks of the manifest, datapacks from modrinth keep their locked version unless
+/// the manifest pins another one.
+pub async fn resolve(
+    context: &mut McContext,
+    game_version: &str,
+    datapacks: &HashMap<String, ManifestDatapack>,
+    locked: &[DatapackLockfileEntry]
+) -> McResult<Vec<DatapackLockfileEntry>> {
+    let mut names: Vec<&String> = datapacks.keys().collect();
+    names.sort();
+
+    let mut entries = Vec::new();
+
+    for name in names {
+        let datapack = &datapacks[name];
+        let source = datapack.source();
+        let locked = locked
+            .iter()
+            .find(|l| l.name == *name && l.source == source);
+
+        if source != ModLockfileSource::Modrinth {
+            entries.push(DatapackLockfileEntry {
+                name: name.clone(),
+                version: None,
+                source,
+                hash: locked.and_then(|l| l.hash.clone()),
+                url: None
+            });
+
+            continue;
+        }
+
+        if let Some(locked) = locked
+            && locked.url.is_some()
+            && datapack
+                .pinned_version()
+                .is_none_or(|v| locked.version.as_deref() == Some(v))
+        {
+            entries.push(locked.clone());
+
+            continue;
+        }
+
+        context.ensure_unlocked(&format!("the datapack `{}`", name))?;
+        context.ensure_online(&format!("resolving the datapack `{}`", name))?;
+
+        let version = match datapack.pinned_version() {
+            Some(version) => {
+                services::modrinth_api::get_project_version(&context.http_client, name, version)
+                    .await?
+            }
+            None => {
+                services::modrinth_api::get_latest_version_for_loader(
+                    &context.http_client,
+                    name,
+                    "datapack",
+                    game_version
+                )
+                .await?
+            }
+        };
+
+        let file = version
+            .files
+            .iter()
+            .find(|f| f.primary)
+            .ok_or_else(|| anyhow::anyhow!("could not find a file to install for {}", name))?;
+
+        _ = context
+            .shell()
+            .status("Locking", format!("datapack {}@{}", name, version.id));
+
+        entries.push(DatapackLockfileEntry {
+            name: name.clone(),
+            version: Some(version.id.clone()),
+            source,
+            hash: Some(file.checksum()?),
+            url: Some(file.url.clone())
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Installs the datapack of `entry` at `output` unless the installed copy matches mc.lock, and
+/// records its hash.
+async fn install(
+    context: &mut McContext,
+    entry: &mut DatapackLockfileEntry,
+    output: &Path
+) -> McResult<()> {
+    if let ModLockfileSource::Path(ref path) = entry.source {
+        let source = context.cwd.join(path);
+
+        if !output.exists() {
+            _ = context.shell().status("Adding", entry.file_name());
+        }
+
+        if source.is_dir() {
+            // directories are mirrored on every sync, there is nothing to compare them with
+            if output.exists() {
+                tokio::fs::remove_dir_all(output).await?;
+            }
+
+            copy_directory(&source, output)
+                .with_context(|| format!("could not copy {}", source.display()))?;
+
+            entry.hash = None;
+        } else {
+            let hash = hash::hash_file(&source, ChecksumAlgorithm::sha256)
+                .await
+                .with_context(|| format!("could not read {}", source.display()))?;
+
+            if !output.exists() || hash::hash_file(output, ChecksumAlgorithm::sha256).await? != hash
+            {
+                tokio::fs::copy(&source, output).await?;
+            }
+
+            entry.hash = Some(hash);
+        }
+
+        return Ok(());
+    }
+
+    if output.exists() {
+        let Some(ref expected) = entry.hash else {
+            // nothing to verify against, trust the file installed before it was locked
+            entry.hash = Some(hash::hash_file(output, ChecksumAlgorithm::sha256).await?);
+
+            return Ok(());
+        };
+
+        if hash::hash_file(output, expected.algorithm()).await? == *expected {
+            return Ok(());
+        }
+
+        _ = context.shell().warn(format!(
+            "{} does not match the hash in mc.lock, downloading it again",
+            entry.file_name()
+        ));
+    } else {
+        _ = context.shell().status("Adding", entry.file_name());
+    }
+
+    context.ensure_online(&format!("downloading the datapack `{}`", entry.name))?;
+
+    let url = match entry.source {
+        ModLockfileSource::Url(ref url) => url.clone(),
+        _ => entry
+            .url
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("could not find a download for {}", entry.name))?
+    };
+
+    let source = ArtifactSource {
+        url,
+        kind: ArtifactKind::File,
+        checksum: entry.hash.clone().map(ChecksumRef::Local)
+    };
+
+    let hash = network::stream_artifact(&context.http_client, source, output)
+        .await
+        .with_context(|| format!("could not install the datapack `{}`", entry.name))?;
+
+    entry.hash = Some(hash);
+
+    Ok(())
+}
+
+fn copy_directory(source: &Path, output: &Path) -> McResult<()> {
+    std::fs::create_dir_all(output)?;
+
+    for entry in std::fs::read_dir(source)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+
+        if path.is_dir() {
+            copy_directory(&path, &output.join(file_name))?;
+        } else {
+            std::fs::copy(&path, output.join(file_name))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use url::Url;
+
+use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::ChecksumRef;
+use crate::crypto::hash;
+use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileEntry;
+use crate::manifest::lock::ModLockfileSource;
+use crate::mods::loader::LoaderKind;
+use crate::mods::mrpack::Mrpack;
+use crate::mods::mrpack::MrpackFile;
+use crate::mods::mrpack::MrpackFileEnv;
+use crate::mods::mrpack::MrpackFileHashes;
+use crate::mods::mrpack::MrpackIndex;
+use crate::network;
+use crate::network::artifact::ArtifactKind;
+use crate::network::artifact::ArtifactSource;
+use crate::services;
+use crate::services::modrinth_api::ModrinthApiSideSupport;
+use crate::utils::errors::McResult;
+use crate::utils::fs::walk;
+
+pub struct ExportMrpackOptions {
+    pub version_id: String,
+    pub overrides: Vec<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub async fn mrpack(context: &mut McContext, options: &ExportMrpackOptions) -> McResult<()> {
+    let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
+        .await
+        .context("could not find mc.toml file")?;
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let Some(ref minecraft) = lockfile.minecraft else {
+        anyhow::bail!(
+            "could not find a locked minecraft version, run `mc update` to create mc.lock"
+        )
+    };
+
+    _ = context.shell().status(
+        "Exporting",
+        format!("{} {}", manifest.name, options.version_id)
+    );
+
+    let mut dependencies = HashMap::new();
+    dependencies.insert(String::from("minecraft"), minecraft.version.clone());
+
+    if let Some(loader) = minecraft.loader_descriptor()? {
+        let name = match loader.product {
+            LoaderKind::Fabric => "fabric-loader"
+        };
+
+        dependencies.insert(String::from(name), loader.version);
+    }
+
+    let instance_path = context.cwd.join("instance");
+    let dir = tempfile::tempdir()?;
+    let mut files = Vec::new();
+    let mut overrides = Vec::new();
+
+    for entry in &lockfile.mods {
+        let url = match entry.source {
+            ModLockfileSource::Modrinth | ModLockfileSource::CurseForge => {
+                entry.url.clone().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "`{}` has no download url in mc.lock, run `mc update` to refresh it",
+                        entry.name
+                    )
+                })?
+            }
+            ModLockfileSource::Url(ref url) => url.clone(),
+            ModLockfileSource::Path(ref path) => {
+                // local mods can't be downloaded by a launcher, they are shipped in the pack
+                overrides.push((
+                    PathBuf::from("mods").join(format!("{}.jar", entry.name)),
+                    context.cwd.join(path)
+                ));
+
+                continue;
+            }
+        };
+
+        let jar = mod_jar(context, entry, &instance_path, dir.path()).await?;
+
+        if !is_allowed_download(&url) {
+            // launchers refuse downloads from other hosts, the jar is shipped in the pack instead
+            overrides.push((
+                PathBuf::from("mods").join(format!("{}.jar", entry.name)),
+                jar
+            ));
+
+            continue;
+        }
+
+        let env = match entry.source {
+            ModLockfileSource::Modrinth if !context.offline => {
+                let project =
+                    services::modrinth_api::get_project(&context.http_client, &entry.name).await?;
+
+                // mrpack only knows required, optional and unsupported
+                let client = match project.client_side {
+                    ModrinthApiSideSupport::Unknown => ModrinthApiSideSupport::Optional,
+                    side => side
+                };
+
+                Some(MrpackFileEnv {
+                    client,
+                    server: ModrinthApiSideSupport::Required
+                })
+            }
+            _ => None
+        };
+
+        files.push(MrpackFile {
+            path: format!("mods/{}.jar", entry.name),
+            hashes: MrpackFileHashes {
+                sha1: hex::encode(hash::hash_file(&jar, ChecksumAlgorithm::sha1).await?.hash()),
+                sha512: hex::encode(
+                    hash::hash_file(&jar, ChecksumAlgorithm::sha512)
+                        .await?
+                        .hash()
+                )
+            },
+            env,
+            downloads: vec![url],
+            file_size: tokio::fs::metadata(&jar).await?.len()
+        });
+    }
+
+    for directory in &options.overrides {
+        if directory
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            anyhow::bail!(
+                "override directories must be inside the instance, found {}",
+                directory.display()
+            );
+        }
+
+        let source = instance_path.join(directory);
+
+        if !source.is_dir() {
+            _ = context.shell().warn(format!(
+                "could not find {} in the instance, skipping it",
+                directory.display()
+            ));
+
+            continue;
+        }
+
+        for file in walk(&source)? {
+            let path = directory.join(file.strip_prefix(&source)?);
+
+            overrides.push((path, file));
+        }
+    }
+
+    let index = MrpackIndex {
+        format_version: 1,
+        game: String::from("minecraft"),
+        version_id: options.version_id.clone(),
+        name: manifest.name.clone(),
+        summary: Some(manifest.description.clone()),
+        files,
+        dependencies
+    };
+
+    let output = options.output.clone().unwrap_or_else(|| {
+        context
+            .cwd
+            .join(format!("{}-{}.mrpack", manifest.name, options.version_id))
+    });
+
+    Mrpack::create(&output, &index, &overrides)?;
+
+    _ = context
+        .shell()
+        .status("Finished", format!("{}", output.display()));
+
+    Ok(())
+}
+
+/// The jar of `entry`, the installed one when it matches mc.lock, otherwise a fresh download
+/// into `dir`.
+async fn mod_jar(
+    context: &mut McContext,
+    entry: &ModLockfileEntry,
+    instance_path: &Path,
+    dir: &Path
+) -> McResult<PathBuf> {
+    let installed = instance_path.join("mods").join(entry.file_name());
+
+    if installed.exists() {
+        let matches = match entry.hash {
+            Some(ref expected) => {
+                hash::hash_file(&installed, expected.algorithm()).await? == *expected
+            }
+            None => true
+        };
+
+        if matches {
+            return Ok(installed);
+        }
+    }
+
+    context.ensure_online(&format!("downloading {}", entry.descriptor()))?;
+
+    let url = match entry.source {
+        ModLockfileSource::Url(ref url) => url.clone(),
+        _ => entry
+            .url
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("could not find a download for {}", entry.name))?
+    };
+
+    let output = dir.join(format!("{}.jar", entry.name));
+    let source = ArtifactSource {
+        url,
+        kind: ArtifactKind::Jar,
+        checksum: entry.hash.clone().map(ChecksumRef::Local)
+    };
+
+    network::stream_artifact(&context.http_client, source, &output).await?;
+
+    Ok(output)
+}
+
+/// Whether launchers accept downloads from `url` in `files[].downloads` of a modpack.
+fn is_allowed_download(url: &Url) -> bool {
+    matches!(
+        url.host_str(),
+        Some("cdn.modrinth.com" | "github.com" | "raw.githubusercontent.com" | "gitlab.com")
+    )
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::context::McContext;
+use crate::minecraft::console::ServerConsole;
+use crate::minecraft::gamerules::MinecraftGameruleValue;
+use crate::utils::errors::McResult;
+
+/// Sets the gamerules of the manifest once the server is ready, only the rules that differ from
+/// the world are changed.
+pub async fn apply(
+    context: &McContext,
+    mut console: ServerConsole,
+    gamerules: &HashMap<String, MinecraftGameruleValue>
+) -> McResult<()> {
+    if gamerules.is_empty() {
+        return Ok(());
+    }
+
+    console.wait_until_ready().await?;
+
+    let mut names: Vec<&String> = gamerules.keys().collect();
+    names.sort();
+
+    for name in names {
+        let value = gamerules[name].to_string();
+
+        let current_prefix = format!("Gamerule {} is currently set to: ", name);
+        let current = console
+            .query(&format!("gamerule {}", name), |line| {
+                line.split_once(&current_prefix)
+                    .map(|(_, v)| v.trim().to_string())
+            })
+            .await?;
+
+        if current == value {
+            continue;
+        }
+
+        let updated_prefix = format!("Gamerule {} is now set to: ", name);
+        console
+            .query(&format!("gamerule {} {}", name, value), |line| {
+                line.contains(&updated_prefix).then_some(())
+            })
+            .await?;
+
+        _ = context.shell().status(
+            "Setting",
+            format!("gamerule {} {} -> {}", name, current, value)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,549 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use toml_edit::DocumentMut;
+use toml_edit::value;
+use url::Url;
+
+use crate::context::McContext;
+use crate::crypto::checksum::ChecksumRef;
+use crate::crypto::checksum::LocalChecksum;
+use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileEntry;
+use crate::manifest::lock::ModLockfileSource;
+use crate::manifest::presets;
+use crate::mods::curseforge_pack::CurseForgePack;
+use crate::mods::mrpack;
+use crate::mods::mrpack::Mrpack;
+use crate::mods::packwiz;
+use crate::mods::packwiz::PackwizIndex;
+use crate::mods::packwiz::PackwizMod;
+use crate::mods::packwiz::PackwizSide;
+use crate::mods::packwiz::PackwizSource;
+use crate::mods::service::ModServiceKind;
+use crate::network;
+use crate::network::artifact::ArtifactKind;
+use crate::network::artifact::ArtifactSource;
+use crate::ops;
+use crate::services;
+use crate::utils::errors::McResult;
+use crate::utils::product_descriptor::RawProductDescriptor;
+
+pub struct ImportMrpackOptions {
+    pub pack: String,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub struct ImportPackwizOptions {
+    pub pack: String,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub struct ImportCurseForgeOptions {
+    pub pack: PathBuf,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub async fn mrpack(context: &mut McContext, options: &ImportMrpackOptions) -> McResult<()> {
+    context.ensure_unlocked("`mc import`")?;
+
+    let dir = tempfile::tempdir()?;
+    let pack_path = if Path::new(&options.pack).is_file() {
+        PathBuf::from(&options.pack)
+    } else {
+        download_mrpack(context, &options.pack, dir.path()).await?
+    };
+
+    let mut pack = Mrpack::open(&pack_path)?;
+    let index = &pack.index;
+    let game_version = index.game_version()?;
+
+    _ = context
+        .shell()
+        .status("Importing", format!("{} {}", index.name, index.version_id));
+
+    let mut document =
+        read_document(context, &options.manifest_path, &index.name, game_version).await?;
+
+    set_minecraft(&mut document, game_version, index.loader()?);
+
+    let mut lockfile = Lockfile::read(&options.lockfile_path).await?;
+    let instance_path = context.cwd.join("instance");
+
+    for file in &index.files {
+        let path = file.relative_path()?;
+
+        if !file.supports_server() {
+            _ = context
+                .shell()
+                .status("Skipping", format!("{} (client only)", path.display()));
+
+            continue;
+        }
+
+        if !mrpack::is_mod(&path) {
+            let output = instance_path.join(&path);
+            let url = file
+                .downloads
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("could not find a download for {}", file.path))?;
+
+            if let Some(parent) = output.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            let source = ArtifactSource {
+                url: url.clone(),
+                kind: ArtifactKind::File,
+                checksum: Some(ChecksumRef::Local(file.checksum()?))
+            };
+
+            network::stream_artifact(&context.http_client, source, &output).await?;
+
+            continue;
+        }
+
+        if let Some((project_id, version_id)) = file.modrinth_version() {
+            let project =
+                services::modrinth_api::get_project(&context.http_client, &project_id).await?;
+
+            document["mods"][&project.slug] = value(version_id);
+
+            continue;
+        }
+
+        let url = file
+            .downloads
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("could not find a download for {}", file.path))?;
+
+        add_remote(
+            &mut document,
+            &mut lockfile,
+            mod_name(&path)?,
+            url.clone(),
+            Some(file.checksum()?)
+        );
+    }
+
+    write_overrides(context, &mut document, pack.server_overrides()?).await?;
+
+    save(
+        context,
+        &document,
+        lockfile,
+        &options.manifest_path,
+        &options.lockfile_path
+    )
+    .await
+}
+
+pub async fn packwiz(context: &mut McContext, options: &ImportPackwizOptions) -> McResult<()> {
+    context.ensure_unlocked("`mc import`")?;
+
+    let source = PackwizSource::new(&options.pack)?;
+
+    if let PackwizSource::Remote(_) = source {
+        context.ensure_online("downloading a modpack")?;
+    }
+
+    let pack = source.pack(&context.http_client).await?;
+
+    _ = context.shell().status("Importing", &pack.name);
+
+    let mut document = read_document(
+        context,
+        &options.manifest_path,
+        &pack.name,
+        &pack.versions.minecraft
+    )
+    .await?;
+
+    set_minecraft(
+        &mut document,
+        &pack.versions.minecraft,
+        pack.versions.loader()?
+    );
+
+    let mut lockfile = Lockfile::read(&options.lockfile_path).await?;
+    let instance_path = context.cwd.join("instance");
+
+    // files of the index are relative to the index itself
+    let index_dir = Path::new(&pack.index.file)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let index = source
+        .read_toml::<PackwizIndex>(&context.http_client, &pack.index.file)
+        .await?;
+
+    let mut overrides = Vec::new();
+
+    for file in &index.files {
+        let path = packwiz::relative_path(&file.file)?;
+        let pack_path = index_dir.join(&path).to_string_lossy().replace('\\', "/");
+
+        if !file.metafile {
+            overrides.push((path, source.read(&context.http_client, &pack_path).await?));
+
+            continue;
+        }
+
+        let metafile = source
+            .read_toml::<PackwizMod>(&context.http_client, &pack_path)
+            .await?;
+
+        if metafile.side == PackwizSide::Client {
+            _ = context
+                .shell()
+                .status("Skipping", format!("{} (client only)", metafile.name));
+
+            continue;
+        }
+
+        let target = path
+            .parent()
+            .map(|p| p.join(&metafile.filename))
+            .unwrap_or_else(|| PathBuf::from(&metafile.filename));
+        let target = packwiz::relative_path(&target.to_string_lossy())?;
+
+        if mrpack::is_mod(&target)
+            && let Some(ref modrinth) = metafile.update.modrinth
+        {
+            let project =
+                services::modrinth_api::get_project(&context.http_client, &modrinth.mod_id).await?;
+
+            let mut table = toml_edit::InlineTable::new();
+            table.insert("version", modrinth.version.as_str().into());
+            document["mods"][&project.slug] = value(table);
+
+            continue;
+        }
+
+        if mrpack::is_mod(&target)
+            && let Some(ref curseforge) = metafile.update.curseforge
+            && services::curseforge_api::has_api_key()
+        {
+            let project =
+                services::curseforge_api::get_mod(&context.http_client, curseforge.project_id)
+                    .await?;
+
+            document["mods"][&project.slug] = value(curseforge_table(curseforge.file_id));
+
+            continue;
+        }
+
+        let Some(ref url) = metafile.download.url else {
+            _ = context.shell().warn(format!(
+                "{} is only available from curseforge, set {} to import it",
+                metafile.name,
+                services::curseforge_api::API_KEY_VARIABLE
+            ));
+
+            continue;
+        };
+
+        if !mrpack::is_mod(&target) {
+            let output = instance_path.join(&target);
+
+            if let Some(parent) = output.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            let source = ArtifactSource {
+                url: url.clone(),
+                kind: ArtifactKind::File,
+                checksum: metafile.download.checksum().map(ChecksumRef::Local)
+            };
+
+            network::stream_artifact(&context.http_client, source, &output).await?;
+
+            continue;
+        }
+
+        add_remote(
+            &mut document,
+            &mut lockfile,
+            metafile_mod_name(&path)?,
+            url.clone(),
+            metafile.download.checksum()
+        );
+    }
+
+    write_overrides(context, &mut document, overrides).await?;
+
+    save(
+        context,
+        &document,
+        lockfile,
+        &options.manifest_path,
+        &options.lockfile_path
+    )
+    .await
+}
+
+pub async fn curseforge(
+    context: &mut McContext,
+    options: &ImportCurseForgeOptions
+) -> McResult<()> {
+    context.ensure_unlocked("`mc import`")?;
+
+    let mut pack = CurseForgePack::open(&options.pack)?;
+    let manifest = &pack.manifest;
+
+    // the manifest only has ids, their names and files can only be found with the api
+    if manifest.files.iter().any(|f| f.required) && !services::curseforge_api::has_api_key() {
+        anyhow::bail!(
+            "importing a curseforge modpack needs a curseforge api key, set it in {}",
+            services::curseforge_api::API_KEY_VARIABLE
+        );
+    }
+
+    _ = context.shell().status(
+        "Importing",
+        format!("{} {}", manifest.name, manifest.version)
+    );
+
+    let mut document = read_document(
+        context,
+        &options.manifest_path,
+        &manifest.name,
+        &manifest.minecraft.version
+    )
+    .await?;
+
+    set_minecraft(
+        &mut document,
+        &manifest.minecraft.version,
+        manifest.minecraft.loader()?
+    );
+
+    for file in &manifest.files {
+        if !file.required {
+            _ = context.shell().status(
+                "Skipping",
+                format!("curseforge project {} (optional)", file.project_id)
+            );
+
+            continue;
+        }
+
+        let project =
+            services::curseforge_api::get_mod(&context.http_client, file.project_id).await?;
+
+        document["mods"][&project.slug] = value(curseforge_table(file.file_id));
+    }
+
+    write_overrides(context, &mut document, pack.overrides()?).await?;
+
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    save(
+        context,
+        &document,
+        lockfile,
+        &options.manifest_path,
+        &options.lockfile_path
+    )
+    .await
+}
+
+/// Writes mc.toml and mc.lock once the mods of the imported manifest resolve, neither is written
+/// when they don't.
+async fn save(
+    context: &mut McContext,
+    document: &DocumentMut,
+    lockfile: Lockfile,
+    manifest_path: &Path,
+    lockfile_path: &Path
+) -> McResult<()> {
+    let manifest_string = document.to_string();
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = ops::mods::resolve_lockfile(context, &manifest, lockfile).await?;
+
+    tokio::fs::write(manifest_path, manifest_string).await?;
+    lockfile.write(lockfile_path).await
+}
+
+fn set_minecraft(
+    document: &mut DocumentMut,
+    game_version: &str,
+    loader: Option<RawProductDescriptor>
+) {
+    document["minecraft"]["version"] = value(game_version);
+
+    if let Some(loader) = loader {
+        document["minecraft"]["loader"] = value(loader.to_string());
+    }
+}
+
+/// The manifest entry of a mod pinned to a curseforge file.
+fn curseforge_table(file_id: u64) -> toml_edit::InlineTable {
+    let mut table = toml_edit::InlineTable::new();
+    table.insert("version", file_id.to_string().into());
+    table.insert("service", ModServiceKind::CurseForge.to_string().into());
+
+    table
+}
+
+/// Adds a mod downloaded from `url` to the manifest.
+fn add_remote(
+    document: &mut DocumentMut,
+    lockfile: &mut Lockfile,
+    name: String,
+    url: Url,
+    hash: Option<LocalChecksum>
+) {
+    let mut table = toml_edit::InlineTable::new();
+    table.insert("url", url.as_str().into());
+    document["mods"][&name] = value(table);
+
+    lockfile.mods.retain(|m| m.name != name);
+
+    // when the pack already knows the hash, there is no need to download the mod to lock it
+    if hash.is_some() {
+        lockfile.mods.push(ModLockfileEntry {
+            name,
+            version: None,
+            version_number: None,
+            source: ModLockfileSource::Url(url),
+            hash,
+            url: None,
+            dependencies: Vec::new(),
+            optional_dependencies: Vec::new()
+        });
+    }
+}
+
+/// Copies the override files of a pack, relative to the instance.
+async fn write_overrides(
+    context: &mut McContext,
+    document: &mut DocumentMut,
+    overrides: Vec<(PathBuf, Vec<u8>)>
+) -> McResult<()> {
+    let instance_path = context.cwd.join("instance");
+
+    for (path, contents) in overrides {
+        // mods are kept with the project so syncing does not remove them from the instance
+        let output = if mrpack::is_mod(&path) {
+            let mut table = toml_edit::InlineTable::new();
+            table.insert("path", path.to_string_lossy().into_owned().into());
+            document["mods"][&mod_name(&path)?] = value(table);
+
+            context.cwd.join(&path)
+        } else {
+            instance_path.join(&path)
+        };
+
+        if let Some(parent) = output.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&output, contents).await?;
+    }
+
+    Ok(())
+}
+
+/// Reads the manifest to extend, or creates one for the imported pack.
+async fn read_document(
+    context: &mut McContext,
+    manifest_path: &Path,
+    name: &str,
+    game_version: &String
+) -> McResult<DocumentMut> {
+    if manifest_path.exists() {
+        let manifest_string = tokio::fs::read_to_string(manifest_path)
+            .await
+            .context("could not read mc.toml file")?;
+
+        return Ok(manifest_string.parse::<DocumentMut>()?);
+    }
+
+    _ = context.shell().warn(
+        "the instance will not start until YOU agree to the Minecraft EULA (https://aka.ms/MinecraftEULA). you can do so by setting `eula = true` in the server section of `mc.toml`"
+    );
+
+    Ok(presets::create_document_base(name, false, game_version))
+}
+
+/// Downloads the latest version of the modpack `slug` from modrinth into `dir`.
+async fn download_mrpack(context: &mut McContext, slug: &String, dir: &Path) -> McResult<PathBuf> {
+    context.ensure_online("downloading a modpack")?;
+
+    let version = services::modrinth_api::get_project_versions(&context.http_client, slug)
+        .await
+        .context(format!(
+            "could not find a modpack file or a modrinth modpack named {}",
+            slug
+        ))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("could not find a version of {}", slug))?;
+
+    let file = version
+        .files
+        .iter()
+        .find(|f| f.primary)
+        .ok_or_else(|| anyhow::anyhow!("could not find a file to download for {}", slug))?;
+
+    let output = dir.join("pack.mrpack");
+    let source = ArtifactSource {
+        url: file.url.clone(),
+        kind: ArtifactKind::File,
+        checksum: Some(ChecksumRef::Local(file.checksum()?))
+    };
+
+    network::stream_artifact(&context.http_client, source, &output).await?;
+
+    Ok(output)
+}
+
+/// The mod a packwiz metafile describes, `lithium-0.12.1.pw.toml` names the mod `lithium-0.12.1`.
+fn metafile_mod_name(path: &Path) -> McResult<String> {
+    mod_name(&path.with_extension(""))
+}
+
+fn mod_name(path: &Path) -> McResult<String> {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow::anyhow!("could not find a mod name for {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mod_names_keep_the_dots_of_their_file() {
+        let name = mod_name(Path::new("mods/lithium-fabric-0.12.1.jar")).unwrap();
+
+        assert_eq!(name, "lithium-fabric-0.12.1");
+        assert_eq!(
+            ModLockfileEntry {
+                name,
+                version: None,
+                version_number: None,
+                source: ModLockfileSource::Path(PathBuf::from("mods/lithium-fabric-0.12.1.jar")),
+                hash: None,
+                url: None,
+                dependencies: Vec::new(),
+                optional_dependencies: Vec::new()
+            }
+            .file_name(),
+            "lithium-fabric-0.12.1.jar"
+        );
+    }
+
+    #[test]
+    fn metafile_mod_names_keep_the_dots_of_their_file() {
+        let name = metafile_mod_name(Path::new("mods/lithium-0.12.1.pw.toml")).unwrap();
+
+        assert_eq!(name, "lithium-0.12.1");
+    }
+}
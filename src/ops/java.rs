@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 
 use crate::context::McContext;
+use crate::crypto::checksum::ChecksumRef;
+use crate::crypto::checksum::LocalChecksum;
 use crate::env::Architecture;
 use crate::env::Platform;
 use crate::java::JavaDescriptor;
@@ -17,10 +19,16 @@ pub struct JavaInstallOptions {
     pub version: JavaDescriptor,
     pub platform: Platform,
     pub architecture: Architecture,
-    pub java_directory: PathBuf
+    pub java_directory: PathBuf,
+
+    /// Expected checksum of the jdk archive, usually coming from the lockfile
+    pub checksum: Option<LocalChecksum>
 }
 
-pub async fn install(context: &mut McContext, options: &JavaInstallOptions) -> McResult<()> {
+pub async fn install(
+    context: &mut McContext,
+    options: &JavaInstallOptions
+) -> McResult<LocalChecksum> {
     let name = options.version.to_string();
     let path = options.java_directory.join(&name);
 
@@ -34,7 +42,7 @@ pub async fn install(context: &mut McContext, options: &JavaInstallOptions) -> M
 
     tokio::fs::create_dir_all(&path).await?;
 
-    let source = match options.version.product {
+    let mut source = match options.version.product {
         JavaVendor::correto => corretto_api::CorrettoApi::jdk_source(
             options.version.version,
             options.platform,
@@ -47,6 +55,10 @@ pub async fn install(context: &mut McContext, options: &JavaInstallOptions) -> M
         )
     };
 
+    if let Some(ref checksum) = options.checksum {
+        source.checksum = Some(ChecksumRef::Local(checksum.clone()));
+    }
+
     network::stream_artifact(&context.http_client, source, &path).await
 }
 
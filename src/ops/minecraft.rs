@@ -1,8 +1,15 @@
 use std::path::PathBuf;
 
+use anyhow::Context;
+
 use crate::context::McContext;
+use crate::crypto::checksum::ChecksumRef;
+use crate::crypto::checksum::LocalChecksum;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileSource;
 use crate::mods::loader::LoaderKind;
 use crate::network;
+use crate::resolvers::loader::LoaderVersionResolver;
 use crate::services;
 use crate::services::minecraft_api::MinecraftApiVersionManifestEntry;
 use crate::services::minecraft_api::MinecraftApiVersionType;
@@ -12,10 +19,19 @@ use crate::utils::product_descriptor::ProductDescriptor;
 pub struct MinecraftInstallOptions {
     pub version: String,
     pub loader: Option<ProductDescriptor<LoaderKind>>,
-    pub minecraft_directory: PathBuf
+    pub minecraft_directory: PathBuf,
+
+    /// Loader installer version, defaults to the latest one
+    pub installer: Option<String>,
+
+    /// Expected checksum of the server jar, usually coming from the lockfile
+    pub checksum: Option<LocalChecksum>
 }
 
-pub async fn install(context: &mut McContext, options: &MinecraftInstallOptions) -> McResult<()> {
+pub async fn install(
+    context: &mut McContext,
+    options: &MinecraftInstallOptions
+) -> McResult<LocalChecksum> {
     let prefix = options
         .loader
         .as_ref()
@@ -33,27 +49,35 @@ pub async fn install(context: &mut McContext, options: &MinecraftInstallOptions)
 
     // TODO: add progress bar
 
-    _ = context.shell().status("Installing", name);
+    _ = context.shell().status("Installing", &name);
 
     tokio::fs::create_dir_all(&directory).await?;
 
     // TODO: use different api based on loader
-    let source = if let Some(ref loader) = options.loader {
+    let mut source = if let Some(ref loader) = options.loader {
         match loader.product {
             LoaderKind::Fabric => {
-                services::fabric_api::artifact_source(
-                    &context.http_client,
-                    loader,
-                    &options.version
-                )
-                .await?
+                let installer = match options.installer {
+                    Some(ref installer) => installer.clone(),
+                    None => {
+                        LoaderVersionResolver::resolve_installer(context, loader.product).await?
+                    }
+                };
+
+                services::fabric_api::artifact_source(loader, &options.version, &installer)?
             }
         }
     } else {
         services::minecraft_api::artifact_source(&context.http_client, &options.version).await?
     };
 
-    network::stream_artifact(&context.http_client, source, &path).await
+    if let Some(ref checksum) = options.checksum {
+        source.checksum = Some(ChecksumRef::Local(checksum.clone()));
+    }
+
+    network::stream_artifact(&context.http_client, source, &path)
+        .await
+        .with_context(|| format!("could not install {}", name))
 }
 
 pub struct MinecraftListOptions {
@@ -140,3 +164,101 @@ pub async fn list_loaders(
 
     Ok(())
 }
+
+pub struct MinecraftCanUpgradeOptions {
+    pub minecraft_version: String,
+    pub lockfile_path: PathBuf
+}
+
+pub async fn can_upgrade(
+    context: &mut McContext,
+    options: &MinecraftCanUpgradeOptions
+) -> McResult<()> {
+    context.ensure_online("`mc minecraft can-upgrade`")?;
+
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let Some(ref minecraft) = lockfile.minecraft else {
+        anyhow::bail!(
+            "could not find a locked minecraft version, run `mc update` to create mc.lock"
+        )
+    };
+
+    let Some(loader) = minecraft.loader_descriptor()? else {
+        _ = context.shell().status(
+            "Compatible",
+            format!(
+                "no loader configured, nothing blocks the upgrade to {}",
+                options.minecraft_version
+            )
+        );
+
+        return Ok(());
+    };
+
+    let mut blockers = Vec::new();
+
+    let loader_versions = match loader.product {
+        LoaderKind::Fabric => services::fabric_api::get_versions_for_game(
+            &context.http_client,
+            &options.minecraft_version
+        )
+    }
+    .await
+    .with_context(|| format!("could not check {} for this version", loader.product))?;
+
+    if loader_versions.is_empty() {
+        _ = context.shell().status(
+            "Blocked",
+            format!("{} has no release for this version", loader.product)
+        );
+
+        blockers.push(loader.product.to_string());
+    }
+
+    for m in &lockfile.mods {
+        if m.source != ModLockfileSource::Modrinth {
+            _ = context.shell().warn(format!(
+                "could not check `{}`, only mods from modrinth can be checked",
+                m.name
+            ));
+
+            continue;
+        }
+
+        let versions = services::modrinth_api::get_versions(
+            &context.http_client,
+            &m.name,
+            loader.product,
+            Some(&options.minecraft_version)
+        )
+        .await?;
+
+        match versions.first() {
+            Some(version) => {
+                _ = context.shell().status(
+                    "Compatible",
+                    format!("{} {}", m.name, version.version_number)
+                );
+            }
+            None => {
+                _ = context.shell().status(
+                    "Blocked",
+                    format!("{} has no release for this version", m.name)
+                );
+
+                blockers.push(m.name.clone());
+            }
+        }
+    }
+
+    if !blockers.is_empty() {
+        anyhow::bail!(
+            "the upgrade to {} is blocked by: {}",
+            options.minecraft_version,
+            blockers.join(", ")
+        );
+    }
+
+    Ok(())
+}
@@ -1,6 +1,17 @@
+pub mod check;
+pub mod config;
+pub mod datapacks;
 pub mod eula;
+pub mod export;
+pub mod gamerules;
+pub mod import;
 pub mod init;
 pub mod java;
 pub mod minecraft;
 pub mod mods;
+pub mod outdated;
+pub mod resource_pack;
 pub mod run;
+pub mod search;
+pub mod tree;
+pub mod update;
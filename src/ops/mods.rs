@@ -1,36 +1,59 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::collections::VecDeque;
+use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context;
+use url::Url;
 
 use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
 use crate::crypto::checksum::ChecksumRef;
 use crate::crypto::checksum::LocalChecksum;
+use crate::crypto::hash;
 use crate::manifest::Manifest;
 use crate::manifest::ManifestMod;
-use crate::manifest::lock::ModLockfile;
+use crate::manifest::lock::Lockfile;
 use crate::manifest::lock::ModLockfileEntry;
 use crate::manifest::lock::ModLockfileSource;
 use crate::mods::loader::LoaderKind;
+use crate::mods::resolver::ModResolver;
+use crate::mods::service::ModDependencyKind;
+use crate::mods::service::ModFile;
+use crate::mods::service::ModServiceKind;
+use crate::mods::service::ModServices;
+use crate::mods::service::ModVersion;
 use crate::network;
 use crate::network::artifact::ArtifactKind;
 use crate::network::artifact::ArtifactSource;
 use crate::services;
-use crate::services::modrinth_api::ModrinthApiDependencyKind;
 use crate::utils::errors::McResult;
 use crate::utils::product_descriptor::ProductDescriptor;
 use crate::utils::product_descriptor::RawProductDescriptor;
 
 pub struct AddModsOptions {
     pub mods: Vec<String>,
+    pub source: Option<AddModSource>,
+    pub service: ModServiceKind,
+    pub sync: bool,
     pub manifest_path: PathBuf,
     pub lockfile_path: PathBuf
 }
 
+/// Where to add a mod from when it does not come from a mod service.
+pub enum AddModSource {
+    Url(Url),
+    Path(PathBuf),
+    GitHub(String)
+}
+
 pub async fn add(context: &mut McContext, options: &AddModsOptions) -> McResult<()> {
+    if options.sync {
+        context.ensure_unlocked("`mc add`")?;
+        context.ensure_online("`mc add`")?;
+    }
+
     let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
         .await
         .context("could not find mc.toml file")?;
@@ -40,45 +63,286 @@ pub async fn add(context: &mut McContext, options: &AddModsOptions) -> McResult<
     let minecraft_version = manifest.minecraft.resolved_version(context).await?;
     let minecraft_loader = manifest.minecraft.loader_descriptor(context).await?;
 
-    // TODO: add more options (ex: --url)
+    let Some(loader) = minecraft_loader else {
+        anyhow::bail!("a loader must be configured in mc.toml before adding to mods");
+    };
+
+    if let Some(ref source) = options.source {
+        if options.mods.len() > 1 {
+            anyhow::bail!("only one mod can be added from a url, a file or a github repository");
+        }
+
+        let (name, value) = direct_mod(context, source, &options.manifest_path).await?;
+        let name = options.mods.first().cloned().unwrap_or(name);
+
+        _ = context
+            .shell()
+            .status("Adding", format!("{} {} to mods", name, value));
+
+        manifest_document["mods"][&name] = toml_edit::value(value);
+    } else {
+        let service = options.service;
+        let services = ModServices::new(&context.http_client);
 
-    if let Some(loader) = minecraft_loader {
         for m in &options.mods {
-            let version = services::modrinth_api::get_latest_version(
-                &context.http_client,
-                m,
-                loader.product,
-                &minecraft_version
-            )
-            .await
-            .context(
-                format!("the mod `{}` could not be found on modrinth for the configured versions and loader", m)
-            )?;
+            let descriptor = RawProductDescriptor::from_str(m)?;
+            let name = &descriptor.product;
+
+            let version = match descriptor.version {
+                Some(ref version) => {
+                    let version = services
+                        .get_version(service, name, version)
+                        .await
+                        .context(format!(
+                            "the version {} of `{}` could not be found on {}",
+                            version, name, service
+                        ))?;
+
+                    if !version.supports(loader.product, &minecraft_version) {
+                        anyhow::bail!(
+                            "the version {} of `{}` is not published for {} on {}",
+                            version.id,
+                            name,
+                            loader.product,
+                            minecraft_version
+                        );
+                    }
+
+                    version
+                }
+                None => services
+                    .get_latest_version(
+                        service,
+                        name,
+                        loader.product,
+                        Some(&minecraft_version)
+                    )
+                    .await
+                    .context(format!(
+                        "the mod `{}` could not be found on {} for the configured versions and loader",
+                        name, service
+                    ))?
+            };
 
-            manifest_document["mods"][m] = toml_edit::value(&version.id);
+            // mods from the default service only need their version
+            let pin = match service {
+                ModServiceKind::Modrinth => {
+                    manifest_document["mods"][name] = toml_edit::value(&version.id);
+
+                    format!("version = \"{}\"", version.id)
+                }
+                _ => {
+                    let mut table = toml_edit::InlineTable::new();
+                    table.insert("version", version.id.as_str().into());
+                    table.insert("service", service.to_string().into());
+                    manifest_document["mods"][name] = toml_edit::value(table);
+
+                    format!("version = \"{}\", service = \"{}\"", version.id, service)
+                }
+            };
 
             _ = context
                 .shell()
-                .status("Adding", format!("{} {} to mods", m, &version.id));
+                .status("Adding", format!("{} {} to mods", name, &version.id));
+
+            let mut optional = Vec::new();
+
+            for dependency in &version.dependencies {
+                if dependency.kind == ModDependencyKind::Optional {
+                    let project = services
+                        .get_project(service, &dependency.project_id)
+                        .await?;
+
+                    optional.push(project.slug);
+                }
+            }
+
+            if !optional.is_empty() {
+                _ = context.shell().note(format!(
+                    "{} has optional dependencies: {}\nenable them with `{} = {{ {}, optional = [...] }}`",
+                    name,
+                    optional.join(", "),
+                    name,
+                    pin
+                ));
+            }
         }
-    } else {
-        anyhow::bail!("a loader must be configured in mc.toml before adding to mods");
     }
 
-    tokio::fs::write(&options.manifest_path, manifest_document.to_string()).await?;
+    save(
+        context,
+        manifest_document,
+        options.sync,
+        &options.manifest_path,
+        &options.lockfile_path
+    )
+    .await
+}
+
+/// Builds the manifest entry of a mod added from `source`, along with a default name for it.
+async fn direct_mod(
+    context: &mut McContext,
+    source: &AddModSource,
+    manifest_path: &Path
+) -> McResult<(String, toml_edit::InlineTable)> {
+    let mut table = toml_edit::InlineTable::new();
+
+    let name = match source {
+        AddModSource::Url(url) => {
+            table.insert("url", url.as_str().into());
+
+            url.path_segments()
+                .and_then(|mut s| s.next_back())
+                .map(|f| f.trim_end_matches(".jar").to_string())
+                .filter(|n| !n.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("could not find a mod name in {}, pass one", url))?
+        }
+        AddModSource::Path(path) => {
+            // paths in mc.toml are relative to the project
+            let project_path = manifest_path.parent().unwrap_or(Path::new(""));
+
+            if !context.cwd.join(project_path).join(path).is_file() {
+                anyhow::bail!("could not find a mod file at {}", path.display());
+            }
+
+            table.insert("path", path.to_string_lossy().into_owned().into());
+
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .ok_or_else(|| anyhow::anyhow!("could not find a mod name in {}", path.display()))?
+        }
+        AddModSource::GitHub(repository) => {
+            let Some((_, repository_name)) = repository.split_once('/') else {
+                anyhow::bail!("github repositories must be given as owner/repo");
+            };
+
+            let release =
+                services::github_api::get_latest_release(&context.http_client, repository).await?;
+
+            let asset = release.mod_jar().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "could not find a mod jar in the release {} of {}",
+                    release.tag_name,
+                    repository
+                )
+            })?;
+
+            table.insert("url", asset.browser_download_url.as_str().into());
+
+            repository_name.to_string()
+        }
+    };
+
+    Ok((name, table))
+}
+
+pub struct AdoptModsOptions {
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+/// Adds the quarantined jars, and the jars dropped in the mods folder, that are found on modrinth
+/// to the manifest.
+pub async fn adopt(context: &mut McContext, options: &AdoptModsOptions) -> McResult<()> {
+    context.ensure_unlocked("`mc mods adopt`")?;
+    context.ensure_online("`mc mods adopt`")?;
+
+    let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
+        .await
+        .context("could not find mc.toml file")?;
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let mut manifest_document = manifest_string.parse::<toml_edit::DocumentMut>()?;
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let instance_path = context.cwd.join("instance");
+    let managed: HashSet<String> = lockfile
+        .mods
+        .iter()
+        .map(|m| m.descriptor().to_string())
+        .collect();
+
+    let mut candidates = jars(&instance_path.join(QUARANTINE_DIRECTORY)).await?;
+
+    for jar in jars(&instance_path.join("mods")).await? {
+        let stem = jar.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+
+        if !managed.contains(stem) {
+            candidates.push(jar);
+        }
+    }
+
+    if candidates.is_empty() {
+        _ = context.shell().note("there are no unmanaged mods to adopt");
+
+        return Ok(());
+    }
+
+    let matches = identify(context, &candidates).await?;
+    let mut adopted = Vec::new();
+
+    for jar in &candidates {
+        let file_name = jar.file_name().unwrap_or_default().to_string_lossy();
 
-    // TODO: should I sync here? or maybe manually edit the lockfile?
+        let Some((_, slug, version)) = matches.iter().find(|(j, _, _)| j == jar) else {
+            _ = context.shell().warn(format!(
+                "could not find {} on modrinth, add it with `mc add --path`",
+                file_name
+            ));
+
+            continue;
+        };
+
+        if manifest.mods.contains_key(slug) {
+            _ = context.shell().warn(format!(
+                "{} is {} which is already in mc.toml, skipping it",
+                file_name, slug
+            ));
+
+            continue;
+        }
+
+        _ = context.shell().status(
+            "Adopting",
+            format!("{} as {} {}", file_name, slug, version.version_number)
+        );
+
+        manifest_document["mods"][slug] = toml_edit::value(&version.id);
+        adopted.push(jar);
+    }
+
+    if adopted.is_empty() {
+        return Ok(());
+    }
+
+    // nothing is written or deleted unless the adopted mods resolve
+    let manifest_string = manifest_document.to_string();
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = resolve_lockfile(context, &manifest, lockfile).await?;
+
+    tokio::fs::write(&options.manifest_path, manifest_string).await?;
+    lockfile.write(&options.lockfile_path).await?;
+
+    // the locked copies are installed on the next sync
+    for jar in adopted {
+        tokio::fs::remove_file(jar).await?;
+    }
 
     Ok(())
 }
 
 pub struct RemoveModsOptions {
     pub mods: Vec<String>,
+    pub sync: bool,
     pub manifest_path: PathBuf,
     pub lockfile_path: PathBuf
 }
 
 pub async fn remove(context: &mut McContext, options: &RemoveModsOptions) -> McResult<()> {
+    if options.sync {
+        context.ensure_unlocked("`mc remove`")?;
+        context.ensure_online("`mc remove`")?;
+    }
+
     let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
         .await
         .context("could not find mc.toml file")?;
@@ -103,135 +367,228 @@ pub async fn remove(context: &mut McContext, options: &RemoveModsOptions) -> McR
         }
     }
 
-    tokio::fs::write(&options.manifest_path, manifest_document.to_string()).await?;
+    save(
+        context,
+        manifest_document,
+        options.sync,
+        &options.manifest_path,
+        &options.lockfile_path
+    )
+    .await
+}
 
-    // TODO: should I sync here? or maybe manually edit the lockfile?
+/// Writes the edited manifest, when `sync` is set the mods are resolved first and mc.lock is
+/// only written along with mc.toml once they resolve.
+async fn save(
+    context: &mut McContext,
+    manifest_document: toml_edit::DocumentMut,
+    sync: bool,
+    manifest_path: &Path,
+    lockfile_path: &Path
+) -> McResult<()> {
+    let manifest_string = manifest_document.to_string();
 
-    Ok(())
+    if !sync {
+        return Ok(tokio::fs::write(manifest_path, manifest_string).await?);
+    }
+
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = Lockfile::read(lockfile_path).await?;
+    let lockfile = resolve_lockfile(context, &manifest, lockfile).await?;
+
+    tokio::fs::write(manifest_path, manifest_string).await?;
+    lockfile.write(lockfile_path).await
+}
+
+/// Resolves the mods of `manifest` against `lockfile`, the returned lockfile drops the entries
+/// that are no longer required. Nothing is written, so callers can save mc.toml and mc.lock
+/// together once resolution succeeded.
+pub async fn resolve_lockfile(
+    context: &mut McContext,
+    manifest: &Manifest,
+    mut lockfile: Lockfile
+) -> McResult<Lockfile> {
+    let Some(loader) = manifest
+        .minecraft
+        .locked_loader_descriptor(context, &lockfile)
+        .await?
+    else {
+        return Ok(lockfile);
+    };
+
+    let game_version = manifest
+        .minecraft
+        .locked_version(context, &lockfile)
+        .await?;
+
+    lockfile.invalidate_mods(&game_version, Some(loader.product));
+
+    let mut mods = flatten(
+        context,
+        &manifest.mods,
+        loader.product,
+        &game_version,
+        &lockfile.mods
+    )
+    .await?;
+
+    restore_hashes(&mut mods, &lockfile.mods);
+    fill_hashes(context, &mut mods).await?;
+
+    for old in &lockfile.mods {
+        if !mods.iter().any(|m| m.name == old.name) {
+            _ = context
+                .shell()
+                .status("Removing", format!("{} from mc.lock", old.descriptor()));
+        }
+    }
+
+    for new in &mods {
+        if !lockfile
+            .mods
+            .iter()
+            .any(|m| m.name == new.name && m.version == new.version)
+        {
+            _ = context.shell().status("Locking", new.descriptor());
+        }
+    }
+
+    lockfile.mods = mods;
+
+    Ok(lockfile)
 }
 
+/// Where jars found in the mods folder without being managed by mc.toml are moved, relative to
+/// the instance.
+pub const QUARANTINE_DIRECTORY: &str = "mods-quarantine";
+
 pub struct SyncModsOptions {
     pub game_version: String,
     pub loader: Option<ProductDescriptor<LoaderKind>>,
     pub mods_path: PathBuf,
-    pub lockfile_path: PathBuf
+    pub quarantine_path: PathBuf
 }
 
 pub async fn sync(
     context: &mut McContext,
     options: &SyncModsOptions,
-    mods: &HashMap<String, ManifestMod>
+    mods: &HashMap<String, ManifestMod>,
+    lockfile: &mut Lockfile
 ) -> McResult<()> {
     if let Some(ref loader) = options.loader {
         tokio::fs::create_dir_all(&options.mods_path).await?;
 
-        let mut new_lockfile =
-            flatten(context, mods, loader.product, &options.game_version).await?;
+        let mut new_lockfile = if context.locked || context.offline {
+            // install exclusively from the lockfile, without resolving anything
+            verify_locked(mods, &lockfile.mods)?;
 
-        let old_lockfile = tokio::fs::read_to_string(&options.lockfile_path)
-            .await
-            .ok()
-            .and_then(|s| toml::from_str::<ModLockfile>(&s).ok())
-            .map(|lockfile| lockfile.mods)
-            .unwrap_or_default();
+            lockfile.mods.clone()
+        } else {
+            let mut new_lockfile = flatten(
+                context,
+                mods,
+                loader.product,
+                &options.game_version,
+                &lockfile.mods
+            )
+            .await?;
 
-        for new in &mut new_lockfile {
-            for old in &old_lockfile {
-                if old.name == new.name && old.version == new.version {
-                    new.hash = old.hash.clone();
+            restore_hashes(&mut new_lockfile, &lockfile.mods);
 
-                    break;
-                }
-            }
-        }
+            new_lockfile
+        };
 
         // TODO: double check filename includes hash when using url source
 
+        // jars installed by mc are named after a locked mod, the other ones were added by hand
+        let managed: HashSet<String> = lockfile
+            .mods
+            .iter()
+            .chain(new_lockfile.iter())
+            .map(|m| m.descriptor().to_string())
+            .collect();
+
         let mut extra_mods = HashSet::new();
-        let mut rd = tokio::fs::read_dir(&options.mods_path).await?;
+        let mut unmanaged = Vec::new();
 
-        while let Some(ref entry) = rd.next_entry().await? {
-            let path = entry.path();
+        for path in jars(&options.mods_path).await? {
             let stem = path
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or_default();
 
-            let product = RawProductDescriptor::from_str(stem)?;
-
-            extra_mods.insert(product);
-        }
-
-        for new in &new_lockfile {
-            let descriptor = &new.descriptor();
-
-            // if already installed
-            if extra_mods.contains(descriptor) {
-                // TODO: should the lockfile hash be validated here?
-                extra_mods.remove(descriptor);
+            if managed.contains(stem) {
+                extra_mods.insert(RawProductDescriptor::from_str(stem)?);
             } else {
-                let name = format!("{}", descriptor);
-                _ = context.shell().status("Adding", &name);
-
-                let output = options.mods_path.join(&name).with_extension("jar");
-
-                match &new.source {
-                    ModLockfileSource::Modrinth => {
-                        let version_string = new.version.clone().ok_or_else(|| {
-                            anyhow::anyhow!(
-                                "could not installed modrinth mod without a specific version"
-                            )
-                        })?;
+                unmanaged.push(path);
+            }
+        }
 
-                        let version = services::modrinth_api::get_version(
-                            &context.http_client,
-                            &version_string
-                        )
-                        .await?;
+        for new in &mut new_lockfile {
+            let descriptor = new.descriptor();
+            let name = format!("{}", descriptor);
+            let output = options.mods_path.join(new.file_name());
 
-                        let file = version.files.iter().find(|f| f.primary).ok_or_else(|| {
-                            anyhow::anyhow!("could not find a file to install for {}", name)
-                        })?;
+            if let ModLockfileSource::Path(ref path) = new.source {
+                let source = context.cwd.join(path);
+                let installed = extra_mods.remove(&descriptor);
+                let locked = new
+                    .hash
+                    .as_ref()
+                    .filter(|_| context.locked || context.offline);
 
-                        let mut checksum = [0u8; 20];
+                new.hash =
+                    Some(copy_local(context, &name, &source, &output, installed, locked).await?);
 
-                        _ = hex::decode_to_slice(&file.hashes.sha1, &mut checksum);
+                continue;
+            }
 
-                        let source = ArtifactSource {
-                            url: file.url.clone(),
-                            kind: ArtifactKind::Jar,
-                            checksum: Some(ChecksumRef::Local(LocalChecksum::sha1(checksum)))
-                        };
+            // if already installed
+            if extra_mods.remove(&descriptor) {
+                let expected = match (&new.hash, new.source.service()) {
+                    (Some(hash), _) => Some(hash.clone()),
+                    (None, Some(service)) if !context.offline => {
+                        Some(service_file(context, service, new).await?.hash)
+                    }
+                    (None, _) => None
+                };
 
-                        network::stream_artifact(&context.http_client, source, &output).await?;
+                let Some(expected) = expected else {
+                    // nothing to verify against, trust the file installed before it was locked
+                    new.hash = Some(hash::hash_file(&output, ChecksumAlgorithm::sha256).await?);
 
-                        // TODO: hash into lockfile
-                    }
-                    ModLockfileSource::Url(url) => {
-                        let source = ArtifactSource {
-                            url: url.clone(),
-                            kind: ArtifactKind::Jar,
-                            checksum: None
-                        };
+                    continue;
+                };
 
-                        network::stream_artifact(&context.http_client, source, &output).await?;
+                if hash::hash_file(&output, expected.algorithm()).await? == expected {
+                    new.hash = Some(expected);
 
-                        // TODO: hash into lockfile
-                    }
+                    continue;
                 }
+
+                _ = context.shell().warn(format!(
+                    "{} does not match the hash in mc.lock, downloading it again",
+                    name
+                ));
+            } else {
+                _ = context.shell().status("Adding", &name);
             }
+
+            new.hash = Some(download(context, new, &output).await?);
         }
 
         for descriptor in extra_mods {
             let name = format!("{}", descriptor);
             _ = context.shell().status("Removing", &name);
 
-            tokio::fs::remove_file(options.mods_path.join(name).with_extension("jar")).await?;
+            tokio::fs::remove_file(options.mods_path.join(format!("{}.jar", name))).await?;
         }
 
-        let lockfile = ModLockfile { mods: new_lockfile };
-        let lockfile_string = toml::to_string_pretty(&lockfile)?;
-        tokio::fs::write(&options.lockfile_path, lockfile_string).await?;
+        if !unmanaged.is_empty() {
+            quarantine(context, &unmanaged, &options.quarantine_path).await?;
+        }
+
+        lockfile.mods = new_lockfile;
     } else {
         if !mods.is_empty() {
             _ = context
@@ -243,86 +600,417 @@ pub async fn sync(
     Ok(())
 }
 
-pub async fn flatten(
+/// Moves jars that are not managed by mc.toml out of the mods folder instead of deleting them, the
+/// ones found on modrinth can be added to mc.toml with `mc mods adopt`.
+async fn quarantine(
     context: &mut McContext,
-    mods: &HashMap<String, ManifestMod>,
-    loader: LoaderKind,
-    game_version: &String
-) -> McResult<Vec<ModLockfileEntry>> {
-    let mut resolved_mods = Vec::new();
-    let mut queue = VecDeque::new();
-    let mut seen = HashSet::new();
+    jars: &[PathBuf],
+    quarantine_path: &Path
+) -> McResult<()> {
+    tokio::fs::create_dir_all(quarantine_path).await?;
 
-    for (name, m) in mods {
-        match m {
-            ManifestMod::Version(v) => {
-                queue.push_back((name.clone(), Some(v.clone())));
+    let mut moved = Vec::new();
+
+    for jar in jars {
+        let Some(file_name) = jar.file_name() else {
+            continue;
+        };
+
+        let output = quarantine_path.join(file_name);
+        tokio::fs::rename(jar, &output).await?;
+
+        _ = context.shell().warn(format!(
+            "{} is not managed by mc.toml, moved it to {}",
+            file_name.to_string_lossy(),
+            quarantine_path.display()
+        ));
+
+        moved.push(output);
+    }
+
+    if context.offline {
+        return Ok(());
+    }
+
+    match identify(context, &moved).await {
+        Ok(matches) => {
+            for (jar, slug, version) in matches {
+                _ = context.shell().note(format!(
+                    "{} is {} {} on modrinth, run `mc mods adopt` to add it to mc.toml",
+                    jar.file_name().unwrap_or_default().to_string_lossy(),
+                    slug,
+                    version.version_number
+                ));
+            }
+        }
+        Err(error) => {
+            _ = context.shell().warn(format!(
+                "could not look up the unmanaged mods on modrinth: {}",
+                error
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks up `jars` on modrinth by hash, returns the slug and version of the ones found.
+async fn identify(
+    context: &mut McContext,
+    jars: &[PathBuf]
+) -> McResult<Vec<(PathBuf, String, ModVersion)>> {
+    let services = ModServices::new(&context.http_client);
+    let mut hashes = Vec::new();
+
+    for jar in jars {
+        let hash = hash::hash_file(jar, ChecksumAlgorithm::sha512).await?;
+
+        hashes.push(hex::encode(hash.hash()));
+    }
+
+    let mut versions = services
+        .get_versions_from_hashes(ModServiceKind::Modrinth, &hashes, ChecksumAlgorithm::sha512)
+        .await?;
+
+    let mut matches = Vec::new();
+
+    for (jar, hash) in jars.iter().zip(hashes) {
+        let Some(version) = versions.remove(&hash) else {
+            continue;
+        };
+
+        let project = services
+            .get_project(ModServiceKind::Modrinth, &version.project_id)
+            .await?;
+
+        matches.push((jar.clone(), project.slug, version));
+    }
+
+    Ok(matches)
+}
+
+/// Lists the jars of `directory`, a missing directory has none.
+async fn jars(directory: &Path) -> McResult<Vec<PathBuf>> {
+    let mut jars = Vec::new();
+
+    if !directory.exists() {
+        return Ok(jars);
+    }
+
+    let mut rd = tokio::fs::read_dir(directory).await?;
+
+    while let Some(ref entry) = rd.next_entry().await? {
+        let path = entry.path();
+
+        if path.is_file() && path.extension().is_some_and(|e| e == "jar") {
+            jars.push(path);
+        }
+    }
+
+    jars.sort();
+
+    Ok(jars)
+}
+
+/// Downloads the jar of `entry` to `output`, verified against the locked hash when there is one,
+/// and returns its hash.
+async fn download(
+    context: &mut McContext,
+    entry: &ModLockfileEntry,
+    output: &Path
+) -> McResult<LocalChecksum> {
+    context.ensure_online(&format!("downloading {}", entry.descriptor()))?;
+
+    let (url, checksum) = match (&entry.source, &entry.url, &entry.hash) {
+        (ModLockfileSource::Url(url), _, hash) => (url.clone(), hash.clone()),
+        (ModLockfileSource::Path(path), _, _) => {
+            anyhow::bail!("could not download local mod {}", path.display())
+        }
+        (_, Some(url), Some(hash)) => (url.clone(), Some(hash.clone())),
+        (source, _, hash) => {
+            let service = source
+                .service()
+                .ok_or_else(|| anyhow::anyhow!("could not find a download for {}", entry.name))?;
+            let file = service_file(context, service, entry).await?;
+
+            (file.url, Some(hash.clone().unwrap_or(file.hash)))
+        }
+    };
+
+    let source = ArtifactSource {
+        url,
+        kind: ArtifactKind::Jar,
+        checksum: checksum.map(ChecksumRef::Local)
+    };
+
+    network::stream_artifact(&context.http_client, source, output)
+        .await
+        .with_context(|| format!("could not install {}", entry.descriptor()))
+}
+
+/// Copies a jar of the project to `output` unless the installed copy is identical, and returns
+/// its hash. When the mod is `locked`, a jar that no longer matches mc.lock is refused.
+async fn copy_local(
+    context: &mut McContext,
+    name: &str,
+    source: &Path,
+    output: &Path,
+    installed: bool,
+    locked: Option<&LocalChecksum>
+) -> McResult<LocalChecksum> {
+    let hash = hash::hash_file(source, ChecksumAlgorithm::sha256)
+        .await
+        .with_context(|| format!("could not read {}", source.display()))?;
+
+    if locked.is_some_and(|locked| *locked != hash) {
+        anyhow::bail!(
+            "{} does not match the hash of `{}` in mc.lock, but --locked or --offline was passed \
+             to prevent updating it",
+            source.display(),
+            name
+        );
+    }
+
+    if installed && hash::hash_file(output, ChecksumAlgorithm::sha256).await? == hash {
+        return Ok(hash);
+    }
+
+    let status = if installed { "Updating" } else { "Adding" };
+    _ = context.shell().status(status, name);
+
+    tokio::fs::copy(source, output).await?;
+
+    Ok(hash)
+}
+
+/// Hashes the url and local mods that are missing a hash, url mods are downloaded to do so.
+pub async fn fill_hashes(context: &mut McContext, mods: &mut [ModLockfileEntry]) -> McResult<()> {
+    for entry in mods.iter_mut().filter(|m| m.hash.is_none()) {
+        let hash = match entry.source {
+            ModLockfileSource::Path(ref path) => {
+                let source = context.cwd.join(path);
+
+                hash::hash_file(&source, ChecksumAlgorithm::sha256)
+                    .await
+                    .with_context(|| format!("could not read {}", source.display()))?
+            }
+            ModLockfileSource::Url(_) => {
+                let dir = tempfile::tempdir()?;
+
+                download(context, entry, &dir.path().join("mod.jar")).await?
             }
-            ManifestMod::Detailed {
-                version,
-                service: _ // TODO: make sure the service is not lost here and hard coded later
-            } => {
-                queue.push_back((name.clone(), Some(version.clone())));
+            ModLockfileSource::Modrinth | ModLockfileSource::CurseForge => continue
+        };
+
+        entry.hash = Some(hash);
+    }
+
+    Ok(())
+}
+
+/// The file of the version locked for `entry` on the service that resolved it.
+async fn service_file(
+    context: &mut McContext,
+    service: ModServiceKind,
+    entry: &ModLockfileEntry
+) -> McResult<ModFile> {
+    context.ensure_online(&format!("fetching the hash of {}", entry.descriptor()))?;
+
+    let version_string = entry.version.clone().ok_or_else(|| {
+        anyhow::anyhow!(
+            "could not install {} mod without a specific version",
+            service
+        )
+    })?;
+
+    let version = ModServices::new(&context.http_client)
+        .get_version(service, &entry.name, &version_string)
+        .await?;
+
+    Ok(version.file)
+}
+
+/// Makes sure the locked mods still match the manifest, so they can be installed without
+/// resolving them again.
+fn verify_locked(mods: &HashMap<String, ManifestMod>, locked: &[ModLockfileEntry]) -> McResult<()> {
+    let entries: HashMap<&String, &ModLockfileEntry> =
+        locked.iter().map(|m| (&m.name, m)).collect();
+    let mut problems = Vec::new();
+
+    for (name, m) in mods {
+        let Some(entry) = entries.get(name) else {
+            problems.push(format!("`{}` is not locked", name));
+
+            continue;
+        };
+
+        let matches = match m.direct_source() {
+            Some(source) => entry.source == source,
+            None => {
+                m.service().map(ModLockfileSource::from).as_ref() == Some(&entry.source)
+                    && m.pinned_version()
+                        .is_none_or(|v| entry.version.as_deref() == Some(v))
             }
-            ManifestMod::Remote { url } => {
-                resolved_mods.push(ModLockfileEntry {
-                    name: name.clone(),
-                    version: None,
-                    source: ModLockfileSource::Url(url.clone()),
-                    hash: None
-                });
+        };
+
+        if !matches {
+            problems.push(format!(
+                "`{}` is locked to a different version or source",
+                name
+            ));
+        }
+
+        for optional in &entry.optional_dependencies {
+            if !m.includes_optional(optional) {
+                problems.push(format!(
+                    "`{}` is locked with the optional dependency `{}`",
+                    name, optional
+                ));
             }
         }
     }
 
-    while let Some((name, version)) = queue.pop_front() {
-        if seen.contains(&name) {
+    let mut reachable = HashSet::new();
+    let mut queue: Vec<&String> = mods.keys().collect();
+
+    while let Some(name) = queue.pop() {
+        if !reachable.insert(name) {
             continue;
         }
 
-        seen.insert(name.clone());
+        if let Some(entry) = entries.get(name) {
+            queue.extend(&entry.dependencies);
+            queue.extend(&entry.optional_dependencies);
+        }
+    }
 
-        let v = if let Some(ref version) = version {
-            services::modrinth_api::get_version(&context.http_client, version).await?
-        } else {
-            services::modrinth_api::get_latest_version(
-                &context.http_client,
-                &name,
-                loader,
-                game_version
-            )
-            .await?
-        };
+    for entry in locked {
+        if !reachable.contains(&entry.name) {
+            problems.push(format!(
+                "`{}` is locked but not required by mc.toml",
+                entry.name
+            ));
+        }
+    }
 
-        resolved_mods.push(ModLockfileEntry {
-            name,
-            version: Some(v.id.clone()),
-            source: ModLockfileSource::Modrinth,
-            hash: None
-        });
-
-        for dependency in v.dependencies {
-            match dependency.dependency_type {
-                ModrinthApiDependencyKind::Optional => {
-                    // TODO: handle optional dependencies
-                }
-                ModrinthApiDependencyKind::Required => {
-                    let project = services::modrinth_api::get_project(
-                        &context.http_client,
-                        &dependency.project_id
-                    )
-                    .await?;
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "mc.lock does not match mc.toml, run `mc update` without --locked, --frozen or \
+             --offline\n  {}",
+            problems.join("\n  ")
+        );
+    }
 
-                    queue.push_back((project.slug, dependency.version_id));
-                }
-                ModrinthApiDependencyKind::Incompatible => {
-                    // TODO: handle incompatibility issues
-                }
-            };
+    Ok(())
+}
+
+/// Copies the hashes of `old` entries into the `new` entries resolved to the same version from the
+/// same source, url and path mods have no version so a changed source needs a new hash.
+pub fn restore_hashes(new: &mut [ModLockfileEntry], old: &[ModLockfileEntry]) {
+    for new in new {
+        for old in old {
+            if old.name == new.name && old.version == new.version && old.source == new.source {
+                new.hash = old.hash.clone();
+
+                break;
+            }
+        }
+    }
+}
+
+/// Resolves the full list of mods to install. Mods that are not pinned by the manifest or by a
+/// dependency keep the version found in `locked` when there is one.
+pub async fn flatten(
+    context: &mut McContext,
+    mods: &HashMap<String, ManifestMod>,
+    loader: LoaderKind,
+    game_version: &String,
+    locked: &[ModLockfileEntry]
+) -> McResult<Vec<ModLockfileEntry>> {
+    let services = ModServices::new(&context.http_client);
+    let mut resolver = ModResolver::new(&services, mods, loader, game_version, locked);
+    let resolved = resolver.resolve().await?;
+
+    for warning in resolver.skipped() {
+        _ = context.shell().warn(warning);
+    }
+
+    let curseforge: Vec<_> = resolved
+        .iter()
+        .filter(|m| m.source == ModLockfileSource::CurseForge)
+        .map(|m| m.name.as_str())
+        .collect();
+
+    if !curseforge.is_empty() {
+        _ = context.shell().warn(format!(
+            "curseforge does not tell which side a mod runs on, make sure these mods run on a \
+             server: {}",
+            curseforge.join(", ")
+        ));
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::shell::Shell;
+
+    fn url_mod(url: &str, hash: Option<&str>) -> ModLockfileEntry {
+        ModLockfileEntry {
+            name: String::from("foo"),
+            version: None,
+            version_number: None,
+            source: ModLockfileSource::Url(Url::parse(url).unwrap()),
+            hash: hash.map(|h| LocalChecksum::from_str(h).unwrap()),
+            url: None,
+            dependencies: Vec::new(),
+            optional_dependencies: Vec::new()
         }
     }
 
-    resolved_mods.sort_by(|a, b| a.name.cmp(&b.name));
+    const HASH: &str = "sha1:a9993e364706816aba3e25717850c26c9cd0d89d";
+
+    #[test]
+    fn restore_hashes_keeps_the_hash_of_an_unchanged_source() {
+        let old = [url_mod("https://example.com/foo-1.jar", Some(HASH))];
+        let mut new = [url_mod("https://example.com/foo-1.jar", None)];
+
+        restore_hashes(&mut new, &old);
+
+        assert_eq!(new[0].hash, old[0].hash);
+    }
+
+    #[test]
+    fn restore_hashes_drops_the_hash_of_a_changed_source() {
+        let old = [url_mod("https://example.com/foo-1.jar", Some(HASH))];
+        let mut new = [url_mod("https://example.com/foo-2.jar", None)];
 
-    Ok(resolved_mods)
+        restore_hashes(&mut new, &old);
+
+        assert_eq!(new[0].hash, None);
+    }
+
+    #[tokio::test]
+    async fn copy_local_refuses_a_jar_that_changed_since_it_was_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = McContext::new(Shell::new(), dir.path().to_path_buf()).unwrap();
+        let source = dir.path().join("foo.jar");
+        let output = dir.path().join("mods.jar");
+        tokio::fs::write(&source, b"foo").await.unwrap();
+
+        let locked = LocalChecksum::from_str(&format!("sha256:{}", "00".repeat(32))).unwrap();
+        let result = copy_local(&mut context, "foo", &source, &output, false, Some(&locked)).await;
+
+        assert!(result.is_err());
+        assert!(!output.exists());
+
+        let hash = copy_local(&mut context, "foo", &source, &output, false, None)
+            .await
+            .unwrap();
+        copy_local(&mut context, "foo", &source, &output, true, Some(&hash))
+            .await
+            .unwrap();
+    }
 }
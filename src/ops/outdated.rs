@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::context::McContext;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileSource;
+use crate::mods::loader::LoaderKind;
+use crate::services;
+use crate::utils::errors::McResult;
+
+pub struct OutdatedOptions {
+    pub json: bool,
+    pub lockfile_path: PathBuf
+}
+
+#[derive(Serialize)]
+struct OutdatedEntry {
+    name: String,
+    current: String,
+    compatible: Option<String>,
+    latest: Option<String>
+}
+
+pub async fn outdated(context: &mut McContext, options: &OutdatedOptions) -> McResult<()> {
+    context.ensure_online("`mc outdated`")?;
+
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let Some(ref minecraft) = lockfile.minecraft else {
+        anyhow::bail!(
+            "could not find a locked minecraft version, run `mc update` to create mc.lock"
+        )
+    };
+
+    let mut entries = Vec::new();
+
+    // MINECRAFT
+
+    let manifest = services::minecraft_api::get_manifest(&context.http_client).await?;
+
+    entries.push(OutdatedEntry {
+        name: String::from("minecraft"),
+        current: minecraft.version.clone(),
+        compatible: Some(manifest.latest.release),
+        latest: manifest.versions.first().map(|v| v.id.clone())
+    });
+
+    // LOADER
+
+    if let Some(loader) = minecraft.loader_descriptor()? {
+        let (compatible, latest) = match loader.product {
+            LoaderKind::Fabric => (
+                services::fabric_api::get_versions_for_game(
+                    &context.http_client,
+                    &minecraft.version
+                )
+                .await?,
+                services::fabric_api::get_versions(&context.http_client).await?
+            )
+        };
+
+        entries.push(OutdatedEntry {
+            name: loader.product.to_string(),
+            current: loader.version.clone(),
+            compatible: compatible.into_iter().next().map(|v| v.version),
+            latest: latest.into_iter().next().map(|v| v.version)
+        });
+
+        // MODS
+
+        for m in &lockfile.mods {
+            let (ModLockfileSource::Modrinth, Some(version)) = (&m.source, &m.version) else {
+                continue;
+            };
+
+            let current =
+                services::modrinth_api::get_version(&context.http_client, version).await?;
+
+            let compatible = services::modrinth_api::get_versions(
+                &context.http_client,
+                &m.name,
+                loader.product,
+                Some(&minecraft.version)
+            )
+            .await?;
+
+            let latest = services::modrinth_api::get_versions(
+                &context.http_client,
+                &m.name,
+                loader.product,
+                None
+            )
+            .await?;
+
+            entries.push(OutdatedEntry {
+                name: m.name.clone(),
+                current: current.version_number,
+                compatible: compatible.into_iter().next().map(|v| v.version_number),
+                latest: latest.into_iter().next().map(|v| v.version_number)
+            });
+        }
+    }
+
+    let mut shell = context.shell();
+    let stdout = shell.out();
+
+    if options.json {
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&entries)?)?;
+
+        return Ok(());
+    }
+
+    let rows: Vec<[&str; 4]> = entries
+        .iter()
+        .map(|e| {
+            [
+                e.name.as_str(),
+                e.current.as_str(),
+                e.compatible.as_deref().unwrap_or("-"),
+                e.latest.as_deref().unwrap_or("-")
+            ]
+        })
+        .collect();
+
+    let header = ["name", "current", "compatible", "latest"];
+    let mut widths = header.map(|h| h.len());
+
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        writeln!(
+            stdout,
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        )?;
+    }
+
+    Ok(())
+}
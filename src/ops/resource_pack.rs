@@ -0,0 +1,95 @@
+use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::manifest::ManifestResourcePack;
+use crate::manifest::lock::ResourcePackLockfileEntry;
+use crate::network;
+use crate::services;
+use crate::utils::errors::McResult;
+
+/// Locks the resource pack of the manifest. The pack is downloaded once to compute the sha1
+/// clients verify it with, the locked entry is kept while it still matches the manifest.
+pub async fn lock(
+    context: &mut McContext,
+    pack: Option<&ManifestResourcePack>,
+    game_version: &str,
+    locked: Option<&ResourcePackLockfileEntry>
+) -> McResult<Option<ResourcePackLockfileEntry>> {
+    let Some(pack) = pack else {
+        return Ok(None);
+    };
+
+    let project = pack.project()?;
+
+    if let Some(locked) = locked
+        && locked.project.as_deref() == project
+        && match project {
+            Some(_) => pack
+                .version
+                .as_ref()
+                .is_none_or(|v| locked.version.as_ref() == Some(v)),
+            None => pack.url.as_ref() == Some(&locked.url)
+        }
+    {
+        return Ok(Some(locked.clone()));
+    }
+
+    context.ensure_unlocked("the resource pack")?;
+    context.ensure_online("resolving the resource pack")?;
+
+    let (version, url) = match project {
+        Some(project) => {
+            let version = match pack.version {
+                Some(ref version) => {
+                    services::modrinth_api::get_project_version(
+                        &context.http_client,
+                        project,
+                        version
+                    )
+                    .await?
+                }
+                None => {
+                    services::modrinth_api::get_latest_version_for_loader(
+                        &context.http_client,
+                        project,
+                        "minecraft",
+                        game_version
+                    )
+                    .await?
+                }
+            };
+
+            let file = version.files.iter().find(|f| f.primary).ok_or_else(|| {
+                anyhow::anyhow!("could not find a file to install for {}", project)
+            })?;
+
+            (Some(version.id.clone()), file.url.clone())
+        }
+        None => (
+            None,
+            pack.url
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("the resource pack needs a `url`"))?
+        )
+    };
+
+    _ = context
+        .shell()
+        .status("Locking", format!("resource pack {}", url));
+
+    let hash =
+        network::hash_remote(&context.http_client, url.clone(), ChecksumAlgorithm::sha1).await?;
+
+    if project.is_none() {
+        _ = context.shell().note(
+            "the hash of the resource pack is kept until its url changes, a new pack uploaded to \
+             the same url needs a new url"
+        );
+    }
+
+    Ok(Some(ResourcePackLockfileEntry {
+        project: project.map(String::from),
+        version,
+        url,
+        hash
+    }))
+}
@@ -5,11 +5,19 @@ use anyhow::Context;
 use tokio::process::Command;
 
 use crate::context::McContext;
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::hash;
 use crate::env::Architecture;
 use crate::env::Platform;
 use crate::manifest::Manifest;
+use crate::manifest::lock::JavaLockfileEntry;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::MinecraftLockfileEntry;
+use crate::minecraft::console::ServerConsole;
 use crate::minecraft::server_properties::ServerProperties;
 use crate::ops;
+use crate::ops::config::SyncConfigOptions;
+use crate::ops::datapacks::SyncDatapacksOptions;
 use crate::ops::eula::EulaApplyOptions;
 use crate::ops::init::InitDirectoriesOptions;
 use crate::ops::java::JavaInstallOptions;
@@ -54,6 +62,8 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
         .await
         .context("could not find mc.toml file")?;
     let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let original_lockfile = Lockfile::read(&options.lockfile_path).await?;
+    let mut lockfile = original_lockfile.clone();
 
     let path = context.cwd.clone();
     let instance_path = path.join("instance");
@@ -79,19 +89,44 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
     // JAVA
 
     let java_directory = path.join("java");
-    let java_path = java_directory.join(manifest.java.version.to_string());
+    let java_version = manifest.java.version_descriptor(context).await?;
+    let java_path = java_directory.join(java_version.to_string());
     let current_platform = Platform::current();
 
-    if !java_path.exists() {
+    let java_locked_hash = lockfile
+        .java
+        .as_ref()
+        .filter(|j| j.version == java_version.to_string())
+        .and_then(|j| j.hash.clone());
+
+    if lockfile
+        .java
+        .as_ref()
+        .is_none_or(|j| j.version != java_version.to_string())
+    {
+        context.ensure_unlocked("the java version")?;
+    }
+
+    let java_hash = if !java_path.exists() {
+        context.ensure_online("installing java")?;
+
         let java_install_options = JavaInstallOptions {
             architecture: Architecture::current(),
             platform: current_platform,
-            version: manifest.java.version_descriptor(context).await?,
-            java_directory
+            version: java_version.clone(),
+            java_directory,
+            checksum: java_locked_hash
         };
 
-        ops::java::install(context, &java_install_options).await?;
-    }
+        Some(ops::java::install(context, &java_install_options).await?)
+    } else {
+        java_locked_hash
+    };
+
+    lockfile.java = Some(JavaLockfileEntry {
+        version: java_version.to_string(),
+        hash: java_hash
+    });
 
     let java_bin = match current_platform {
         Platform::Windows => "javaw.exe",
@@ -102,11 +137,37 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
     // MINECRAFT
 
     let minecraft_directory = path.join("minecraft");
-    let minecraft_version = manifest.minecraft.resolved_version(context).await?;
-    let minecraft_loader = manifest.minecraft.loader_descriptor(context).await?;
-    let minecraft_descriptor_prefix = minecraft_loader
-        .as_ref()
-        .map(|l| l.to_string())
+    let minecraft_version = manifest
+        .minecraft
+        .locked_version(context, &lockfile)
+        .await?;
+
+    // the gamerules are applied once the server is up, rejected ones would only show in its log
+    let gamerule_errors = ops::check::check_gamerules(context, &manifest, &minecraft_version);
+
+    if gamerule_errors > 0 {
+        anyhow::bail!(
+            "found {} problem(s) in the gamerules of mc.toml",
+            gamerule_errors
+        );
+    }
+
+    let minecraft_loader = manifest
+        .minecraft
+        .locked_loader_descriptor(context, &lockfile)
+        .await?;
+    let minecraft_installer = match minecraft_loader {
+        Some(ref loader) => Some(
+            manifest
+                .minecraft
+                .locked_installer_version(context, &lockfile, loader)
+                .await?
+        ),
+        None => None
+    };
+    let minecraft_loader_string = minecraft_loader.as_ref().map(|l| l.to_string());
+    let minecraft_descriptor_prefix = minecraft_loader_string
+        .clone()
         .unwrap_or(String::from("minecraft"));
     let minecraft_descriptor = format!("{}-{}", minecraft_descriptor_prefix, minecraft_version);
 
@@ -114,23 +175,78 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
         .join(minecraft_descriptor)
         .join("server.jar");
 
-    if !minecraft_path.exists() {
+    let minecraft_locked_hash = lockfile
+        .minecraft
+        .as_ref()
+        .filter(|m| {
+            m.version == minecraft_version
+                && m.loader == minecraft_loader_string
+                && m.installer == minecraft_installer
+        })
+        .and_then(|m| m.hash.clone());
+
+    // the installed jar is verified on every run, a jar that does not match is installed again
+    if let Some(ref expected) = minecraft_locked_hash
+        && minecraft_path.exists()
+        && hash::hash_file(&minecraft_path, expected.algorithm()).await? != *expected
+    {
+        _ = context.shell().warn(format!(
+            "{} does not match the hash in mc.lock, installing it again",
+            minecraft_path.display()
+        ));
+
+        tokio::fs::remove_file(&minecraft_path).await?;
+    }
+
+    let minecraft_hash = if !minecraft_path.exists() {
+        context.ensure_online("installing minecraft")?;
+
         let minecraft_install_options = MinecraftInstallOptions {
             version: minecraft_version.clone(),
             loader: minecraft_loader.clone(),
-            minecraft_directory
+            minecraft_directory,
+            installer: minecraft_installer.clone(),
+            checksum: minecraft_locked_hash
         };
 
-        ops::minecraft::install(context, &minecraft_install_options).await?;
-    }
+        ops::minecraft::install(context, &minecraft_install_options).await?
+    } else if let Some(hash) = minecraft_locked_hash {
+        // verified above
+        hash
+    } else {
+        // the jar was installed before it was tracked by the lockfile
+        hash::hash_file(&minecraft_path, ChecksumAlgorithm::sha256).await?
+    };
+
+    lockfile.invalidate_mods(
+        &minecraft_version,
+        minecraft_loader.as_ref().map(|l| l.product)
+    );
+
+    lockfile.minecraft = Some(MinecraftLockfileEntry {
+        version: minecraft_version.clone(),
+        loader: minecraft_loader_string,
+        installer: minecraft_installer,
+        hash: Some(minecraft_hash)
+    });
 
     // TODO: fetch capabilities
 
+    // RESOURCE PACK
+
+    lockfile.resource_pack = ops::resource_pack::lock(
+        context,
+        manifest.server.resource_pack.as_ref(),
+        &minecraft_version,
+        lockfile.resource_pack.as_ref()
+    )
+    .await?;
+
     // PROPERTIES
 
     let mut properties = ServerProperties::default();
 
-    properties.apply(&manifest);
+    properties.apply(&manifest, &lockfile);
 
     tokio::fs::write(
         instance_path.join("server.properties"),
@@ -143,11 +259,39 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
     let sync_options = SyncModsOptions {
         game_version: minecraft_version.clone(),
         loader: minecraft_loader.clone(),
-        lockfile_path: options.lockfile_path.clone(),
-        mods_path: instance_path.join("mods")
+        mods_path: instance_path.join("mods"),
+        quarantine_path: instance_path.join(ops::mods::QUARANTINE_DIRECTORY)
     };
 
-    ops::mods::sync(context, &sync_options, &manifest.mods).await?;
+    ops::mods::sync(context, &sync_options, &manifest.mods, &mut lockfile).await?;
+
+    // DATAPACKS
+
+    let sync_options = SyncDatapacksOptions {
+        game_version: minecraft_version.clone(),
+        datapacks_path: instance_path
+            .join(properties.level_name())
+            .join("datapacks")
+    };
+
+    ops::datapacks::sync(context, &sync_options, &manifest.datapacks, &mut lockfile).await?;
+
+    // CONFIG
+
+    let config_options = SyncConfigOptions {
+        source_path: path.join(ops::config::CONFIG_DIRECTORY),
+        config_path: instance_path.join("config"),
+        state_path: instance_path.join(ops::config::CONFIG_STATE_FILE),
+        variables: ops::config::variables(&manifest, &minecraft_version)
+    };
+
+    ops::config::sync(context, &config_options).await?;
+
+    if lockfile != original_lockfile {
+        context.ensure_unlocked("the installed files")?;
+
+        lockfile.write(&options.lockfile_path).await?;
+    }
 
     // PROCESS
 
@@ -158,8 +302,8 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
         .arg(minecraft_path.as_os_str())
         .arg("--nogui")
         .current_dir(&instance_path)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .kill_on_drop(true);
 
@@ -170,10 +314,34 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
 
     let mut child = command.spawn()?;
 
+    // GAMERULES
+
+    let console = ServerConsole::new(
+        child
+            .stdin
+            .take()
+            .context("could not open the server console")?,
+        child
+            .stdout
+            .take()
+            .context("could not read the server log")?
+    );
+
+    let gamerules = async {
+        if let Err(e) = ops::gamerules::apply(context, console, &manifest.gamerules).await {
+            _ = context
+                .shell()
+                .warn(format!("could not apply the gamerules: {:#}", e));
+        }
+
+        std::future::pending::<()>().await
+    };
+
     tokio::select! {
         _ = child.wait() => {
 
         }
+        _ = gamerules => {}
         _ = tokio::signal::ctrl_c() => {
             // TODO: rcon save + stop instead of kill
             // TODO: release the lock
@@ -182,6 +350,8 @@ pub async fn run(context: &mut McContext, options: &RunOptions) -> McResult<()>
         }
     };
 
+    ops::config::report_drift(context, &config_options).await?;
+
     // TODO: live backups
 
     Ok(())
@@ -0,0 +1,205 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::context::McContext;
+use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
+use crate::mods::service::ModServiceKind;
+use crate::ops;
+use crate::ops::mods::AddModsOptions;
+use crate::services;
+use crate::utils::errors::McResult;
+
+pub struct SearchOptions {
+    pub query: String,
+    pub limit: usize,
+    pub add: bool,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+struct SearchEntry {
+    slug: String,
+    downloads: String,
+    version: String,
+    description: String
+}
+
+pub async fn search(context: &mut McContext, options: &SearchOptions) -> McResult<()> {
+    context.ensure_online("`mc search`")?;
+
+    let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
+        .await
+        .context("could not find mc.toml file")?;
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let lockfile = Lockfile::read(&options.lockfile_path).await?;
+
+    let minecraft_version = manifest
+        .minecraft
+        .locked_version(context, &lockfile)
+        .await?;
+    let Some(loader) = manifest
+        .minecraft
+        .locked_loader_descriptor(context, &lockfile)
+        .await?
+    else {
+        anyhow::bail!("a loader must be configured in mc.toml before searching for mods");
+    };
+
+    let facets = vec![
+        vec![String::from("project_type:mod")],
+        vec![format!("categories:{}", loader.product)],
+        vec![format!("versions:{}", minecraft_version)],
+        vec![
+            String::from("server_side:required"),
+            String::from("server_side:optional"),
+        ],
+    ];
+
+    let results = services::modrinth_api::search(
+        &context.http_client,
+        &options.query,
+        &facets,
+        options.limit
+    )
+    .await?;
+
+    if results.hits.is_empty() {
+        _ = context.shell().warn(format!(
+            "could not find mods matching `{}` for {} {}",
+            options.query, loader.product, minecraft_version
+        ));
+
+        return Ok(());
+    }
+
+    let mut entries = Vec::new();
+
+    for hit in results.hits {
+        let version = services::modrinth_api::get_latest_version(
+            &context.http_client,
+            &hit.slug,
+            loader.product,
+            &minecraft_version
+        )
+        .await
+        .map(|v| v.version_number)
+        .unwrap_or_else(|_| String::from("-"));
+
+        entries.push(SearchEntry {
+            slug: hit.slug,
+            downloads: hit.downloads.to_string(),
+            version,
+            description: hit.description
+        });
+    }
+
+    print_entries(context, &entries, options.add)?;
+
+    if !options.add {
+        return Ok(());
+    }
+
+    let mods = pick(context, &entries)?;
+
+    if mods.is_empty() {
+        return Ok(());
+    }
+
+    let add_options = AddModsOptions {
+        mods,
+        source: None,
+        service: ModServiceKind::Modrinth,
+        sync: true,
+        manifest_path: options.manifest_path.clone(),
+        lockfile_path: options.lockfile_path.clone()
+    };
+
+    ops::mods::add(context, &add_options).await
+}
+
+fn print_entries(context: &mut McContext, entries: &[SearchEntry], numbered: bool) -> McResult<()> {
+    let rows: Vec<[String; 5]> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            [
+                format!("{}", i + 1),
+                e.slug.clone(),
+                e.downloads.clone(),
+                e.version.clone(),
+                e.description.clone()
+            ]
+        })
+        .collect();
+
+    let header = ["#", "slug", "downloads", "version", "description"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+
+    // the index column is only useful to pick mods
+    let skip = if numbered { 0 } else { 1 };
+
+    let mut shell = context.shell();
+    let stdout = shell.out();
+
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .skip(skip)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        writeln!(stdout, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+/// Asks which of the `entries` to add, by number.
+fn pick(context: &mut McContext, entries: &[SearchEntry]) -> McResult<Vec<String>> {
+    {
+        let mut shell = context.shell();
+        let stderr = shell.err();
+
+        write!(
+            stderr,
+            "select the mods to add (ex: 1 3), leave empty to cancel: "
+        )?;
+        stderr.flush()?;
+    }
+
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("could not read the selection")?;
+
+    let mut mods = Vec::new();
+
+    for selection in input.split(|c: char| c.is_whitespace() || c == ',') {
+        if selection.is_empty() {
+            continue;
+        }
+
+        let entry = selection
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| entries.get(i))
+            .ok_or_else(|| anyhow::anyhow!("invalid selection `{}`", selection))?;
+
+        if !mods.contains(&entry.slug) {
+            mods.push(entry.slug.clone());
+        }
+    }
+
+    Ok(mods)
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::context::McContext;
+use crate::manifest::Manifest;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::ModLockfileEntry;
+use crate::manifest::lock::ModLockfileSource;
+use crate::utils::errors::McResult;
+
+pub struct TreeOptions {
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub struct WhyOptions {
+    pub name: String,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+/// The locked mod graph, rooted at the mods listed in mc.toml.
+struct ModTree {
+    roots: Vec<String>,
+    entries: HashMap<String, ModLockfileEntry>
+}
+
+impl ModTree {
+    async fn read(manifest_path: &Path, lockfile_path: &Path) -> McResult<ModTree> {
+        let manifest_string = tokio::fs::read_to_string(manifest_path)
+            .await
+            .context("could not find mc.toml file")?;
+        let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+        let lockfile = Lockfile::read(lockfile_path).await?;
+
+        let mut roots: Vec<String> = manifest.mods.keys().cloned().collect();
+        roots.sort();
+
+        let entries = lockfile
+            .mods
+            .into_iter()
+            .map(|m| (m.name.clone(), m))
+            .collect();
+
+        Ok(ModTree { roots, entries })
+    }
+
+    /// The dependencies of `name`, with whether the edge is optional.
+    fn edges(&self, name: &str) -> Vec<(&String, bool)> {
+        let Some(entry) = self.entries.get(name) else {
+            return Vec::new();
+        };
+
+        entry
+            .dependencies
+            .iter()
+            .map(|d| (d, false))
+            .chain(entry.optional_dependencies.iter().map(|d| (d, true)))
+            .collect()
+    }
+
+    fn label(&self, name: &str, optional: bool) -> String {
+        let version = match self.entries.get(name) {
+            Some(ModLockfileEntry {
+                source: ModLockfileSource::Modrinth | ModLockfileSource::CurseForge,
+                version: Some(version),
+                ..
+            }) => version.clone(),
+            Some(entry) => entry.source.to_string(),
+            None => String::from("(not locked)")
+        };
+
+        if optional {
+            format!("{} {} (optional)", name, version)
+        } else {
+            format!("{} {}", name, version)
+        }
+    }
+}
+
+pub async fn tree(context: &mut McContext, options: &TreeOptions) -> McResult<()> {
+    let tree = ModTree::read(&options.manifest_path, &options.lockfile_path).await?;
+
+    let mut lines = Vec::new();
+    let mut printed = HashSet::new();
+
+    for root in &tree.roots {
+        lines.push(tree.label(root, false));
+        printed.insert(root.clone());
+
+        let mut ancestors = vec![root.clone()];
+        tree_lines(&tree, root, "", &mut ancestors, &mut printed, &mut lines);
+    }
+
+    let mut shell = context.shell();
+    let stdout = shell.out();
+
+    for line in lines {
+        writeln!(stdout, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn tree_lines(
+    tree: &ModTree,
+    name: &str,
+    prefix: &str,
+    ancestors: &mut Vec<String>,
+    printed: &mut HashSet<String>,
+    lines: &mut Vec<String>
+) {
+    let edges = tree.edges(name);
+
+    for (i, (dependency, optional)) in edges.iter().enumerate() {
+        let last = i == edges.len() - 1;
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        let label = tree.label(dependency, *optional);
+
+        // subtrees are only expanded once, cycles are never followed
+        if ancestors.contains(dependency) || !printed.insert((*dependency).clone()) {
+            if tree.edges(dependency).is_empty() {
+                lines.push(format!("{}{}{}", prefix, branch, label));
+            } else {
+                lines.push(format!("{}{}{} (*)", prefix, branch, label));
+            }
+
+            continue;
+        }
+
+        lines.push(format!("{}{}{}", prefix, branch, label));
+
+        ancestors.push((*dependency).clone());
+        tree_lines(
+            tree,
+            dependency,
+            &format!("{}{}", prefix, indent),
+            ancestors,
+            printed,
+            lines
+        );
+        ancestors.pop();
+    }
+}
+
+pub async fn why(context: &mut McContext, options: &WhyOptions) -> McResult<()> {
+    let tree = ModTree::read(&options.manifest_path, &options.lockfile_path).await?;
+
+    if !tree.entries.contains_key(&options.name) && !tree.roots.contains(&options.name) {
+        anyhow::bail!("could not find `{}` in mc.lock", options.name);
+    }
+
+    let mut paths = Vec::new();
+
+    for root in &tree.roots {
+        let mut path = vec![tree.label(root, false)];
+        let mut ancestors = vec![root.clone()];
+
+        why_paths(
+            &tree,
+            root,
+            &options.name,
+            &mut path,
+            &mut ancestors,
+            &mut paths
+        );
+    }
+
+    if paths.is_empty() {
+        anyhow::bail!(
+            "`{}` is locked but no mod in mc.toml depends on it, run `mc update` to refresh mc.lock",
+            options.name
+        );
+    }
+
+    let mut shell = context.shell();
+    let stdout = shell.out();
+
+    for path in paths {
+        writeln!(stdout, "mc.toml -> {}", path.join(" -> "))?;
+    }
+
+    Ok(())
+}
+
+fn why_paths(
+    tree: &ModTree,
+    name: &str,
+    target: &str,
+    path: &mut Vec<String>,
+    ancestors: &mut Vec<String>,
+    paths: &mut Vec<Vec<String>>
+) {
+    if name == target {
+        paths.push(path.clone());
+
+        return;
+    }
+
+    for (dependency, optional) in tree.edges(name) {
+        if ancestors.contains(dependency) {
+            continue;
+        }
+
+        path.push(tree.label(dependency, optional));
+        ancestors.push(dependency.clone());
+
+        why_paths(tree, dependency, target, path, ancestors, paths);
+
+        ancestors.pop();
+        path.pop();
+    }
+}
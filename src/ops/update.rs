@@ -0,0 +1,300 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::context::McContext;
+use crate::manifest::Manifest;
+use crate::manifest::lock::DatapackLockfileEntry;
+use crate::manifest::lock::JavaLockfileEntry;
+use crate::manifest::lock::Lockfile;
+use crate::manifest::lock::MinecraftLockfileEntry;
+use crate::manifest::lock::ModLockfileEntry;
+use crate::manifest::lock::ResourcePackLockfileEntry;
+use crate::ops;
+use crate::resolvers::loader::LoaderVersionResolver;
+use crate::utils::errors::McResult;
+
+pub struct UpdateOptions {
+    pub names: Vec<String>,
+    pub dry_run: bool,
+    pub manifest_path: PathBuf,
+    pub lockfile_path: PathBuf
+}
+
+pub async fn update(context: &mut McContext, options: &UpdateOptions) -> McResult<()> {
+    if !options.dry_run {
+        context.ensure_unlocked("`mc update`")?;
+    }
+
+    context.ensure_online("updating mc.lock")?;
+
+    let manifest_string = tokio::fs::read_to_string(&options.manifest_path)
+        .await
+        .context("could not find mc.toml file")?;
+    let manifest = toml::from_str::<Manifest>(&manifest_string)?;
+    let old_lockfile = Lockfile::read(&options.lockfile_path).await?;
+    let mut lockfile = old_lockfile.clone();
+
+    for name in &options.names {
+        let known = ["minecraft", "loader", "java", "resource-pack"].contains(&name.as_str())
+            || manifest
+                .minecraft
+                .loader
+                .as_ref()
+                .is_some_and(|l| l.product == *name)
+            || manifest.mods.contains_key(name)
+            || manifest.datapacks.contains_key(name)
+            || old_lockfile.mods.iter().any(|m| m.name == *name)
+            || old_lockfile.datapacks.iter().any(|d| d.name == *name);
+
+        if !known {
+            anyhow::bail!(
+                "`{}` is not a mod, a datapack, `minecraft`, `loader`, `java` or `resource-pack`",
+                name
+            );
+        }
+    }
+
+    let is_selected =
+        |name: &str| options.names.is_empty() || options.names.iter().any(|n| n == name);
+
+    // MINECRAFT
+
+    let minecraft_version = if is_selected("minecraft") {
+        manifest.minecraft.resolved_version(context).await?
+    } else {
+        manifest
+            .minecraft
+            .locked_version(context, &old_lockfile)
+            .await?
+    };
+
+    let loader_selected = manifest
+        .minecraft
+        .loader
+        .as_ref()
+        .is_some_and(|l| is_selected("loader") || is_selected(&l.product));
+
+    let minecraft_loader = if loader_selected {
+        manifest.minecraft.loader_descriptor(context).await?
+    } else {
+        manifest
+            .minecraft
+            .locked_loader_descriptor(context, &old_lockfile)
+            .await?
+    };
+
+    let minecraft_installer = match minecraft_loader {
+        Some(ref loader) if loader_selected => {
+            Some(LoaderVersionResolver::resolve_installer(context, loader.product).await?)
+        }
+        Some(ref loader) => Some(
+            manifest
+                .minecraft
+                .locked_installer_version(context, &old_lockfile, loader)
+                .await?
+        ),
+        None => None
+    };
+
+    let minecraft_loader_string = minecraft_loader.as_ref().map(|l| l.to_string());
+
+    let minecraft_hash = old_lockfile
+        .minecraft
+        .as_ref()
+        .filter(|m| {
+            m.version == minecraft_version
+                && m.loader == minecraft_loader_string
+                && m.installer == minecraft_installer
+        })
+        .and_then(|m| m.hash.clone());
+
+    lockfile.invalidate_mods(
+        &minecraft_version,
+        minecraft_loader.as_ref().map(|l| l.product)
+    );
+
+    lockfile.minecraft = Some(MinecraftLockfileEntry {
+        version: minecraft_version.clone(),
+        loader: minecraft_loader_string,
+        installer: minecraft_installer,
+        hash: minecraft_hash
+    });
+
+    // JAVA
+
+    let java_version = manifest.java.version_descriptor(context).await?.to_string();
+    let java_hash = old_lockfile
+        .java
+        .as_ref()
+        .filter(|j| j.version == java_version && !is_selected("java"))
+        .and_then(|j| j.hash.clone());
+
+    lockfile.java = Some(JavaLockfileEntry {
+        version: java_version,
+        hash: java_hash
+    });
+
+    // MODS
+
+    if let Some(ref loader) = minecraft_loader {
+        lockfile.mods.retain(|m| !is_selected(&m.name));
+
+        let mut mods = ops::mods::flatten(
+            context,
+            &manifest.mods,
+            loader.product,
+            &minecraft_version,
+            &lockfile.mods
+        )
+        .await?;
+
+        ops::mods::restore_hashes(&mut mods, &old_lockfile.mods);
+
+        if !options.dry_run {
+            ops::mods::fill_hashes(context, &mut mods).await?;
+        }
+
+        lockfile.mods = mods;
+    }
+
+    // DATAPACKS
+
+    lockfile
+        .datapacks
+        .retain(|d| d.source.service().is_none() || !is_selected(&d.name));
+
+    lockfile.datapacks = ops::datapacks::resolve(
+        context,
+        &minecraft_version,
+        &manifest.datapacks,
+        &lockfile.datapacks
+    )
+    .await?;
+
+    // RESOURCE PACK
+
+    let locked_resource_pack = lockfile
+        .resource_pack
+        .take()
+        .filter(|_| !is_selected("resource-pack"));
+
+    lockfile.resource_pack = ops::resource_pack::lock(
+        context,
+        manifest.server.resource_pack.as_ref(),
+        &minecraft_version,
+        locked_resource_pack.as_ref()
+    )
+    .await?;
+
+    print_changes(context, &old_lockfile, &lockfile);
+
+    if options.dry_run {
+        _ = context.shell().warn("not updating lockfile due to dry run");
+    } else {
+        lockfile.write(&options.lockfile_path).await?;
+    }
+
+    Ok(())
+}
+
+fn print_changes(context: &mut McContext, old: &Lockfile, new: &Lockfile) {
+    print_change(
+        context,
+        "minecraft",
+        old.minecraft.as_ref().map(|m| m.version.clone()),
+        new.minecraft.as_ref().map(|m| m.version.clone())
+    );
+
+    print_change(
+        context,
+        "loader",
+        old.minecraft.as_ref().and_then(|m| m.loader.clone()),
+        new.minecraft.as_ref().and_then(|m| m.loader.clone())
+    );
+
+    print_change(
+        context,
+        "installer",
+        old.minecraft.as_ref().and_then(|m| m.installer.clone()),
+        new.minecraft.as_ref().and_then(|m| m.installer.clone())
+    );
+
+    print_change(
+        context,
+        "java",
+        old.java.as_ref().map(|j| j.version.clone()),
+        new.java.as_ref().map(|j| j.version.clone())
+    );
+
+    let mut mods: BTreeMap<&str, (Option<String>, Option<String>)> = BTreeMap::new();
+
+    for m in &old.mods {
+        mods.entry(&m.name).or_default().0 = Some(mod_version_string(m));
+    }
+
+    for m in &new.mods {
+        mods.entry(&m.name).or_default().1 = Some(mod_version_string(m));
+    }
+
+    for (name, (old, new)) in mods {
+        print_change(context, name, old, new);
+    }
+
+    let mut datapacks: BTreeMap<&str, (Option<String>, Option<String>)> = BTreeMap::new();
+
+    for d in &old.datapacks {
+        datapacks.entry(&d.name).or_default().0 = Some(datapack_version_string(d));
+    }
+
+    for d in &new.datapacks {
+        datapacks.entry(&d.name).or_default().1 = Some(datapack_version_string(d));
+    }
+
+    for (name, (old, new)) in datapacks {
+        print_change(context, &format!("datapack {}", name), old, new);
+    }
+
+    print_change(
+        context,
+        "resource-pack",
+        old.resource_pack.as_ref().map(resource_pack_version_string),
+        new.resource_pack.as_ref().map(resource_pack_version_string)
+    );
+}
+
+fn print_change(context: &mut McContext, name: &str, old: Option<String>, new: Option<String>) {
+    let mut shell = context.shell();
+
+    _ = match (old, new) {
+        (Some(old), Some(new)) if old != new => {
+            shell.status("Updating", format!("{} {} -> {}", name, old, new))
+        }
+        (None, Some(new)) => shell.status("Adding", format!("{} {}", name, new)),
+        (Some(old), None) => shell.status("Removing", format!("{} {}", name, old)),
+        _ => Ok(())
+    };
+}
+
+fn mod_version_string(entry: &ModLockfileEntry) -> String {
+    entry
+        .version_number
+        .clone()
+        .or_else(|| entry.version.clone())
+        .unwrap_or_else(|| entry.source.to_string())
+}
+
+fn datapack_version_string(entry: &DatapackLockfileEntry) -> String {
+    entry
+        .version
+        .clone()
+        .unwrap_or_else(|| entry.source.to_string())
+}
+
+fn resource_pack_version_string(entry: &ResourcePackLockfileEntry) -> String {
+    entry
+        .version
+        .clone()
+        .unwrap_or_else(|| entry.url.to_string())
+}
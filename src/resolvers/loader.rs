@@ -1,6 +1,7 @@
 use crate::context::McContext;
 use crate::mods::loader::LoaderKind;
 use crate::resolvers::fabric::FabricVersionResolver;
+use crate::services;
 use crate::utils;
 use crate::utils::errors::McResult;
 use crate::utils::product_descriptor::ProductDescriptor;
@@ -26,3 +27,16 @@ impl VersionResolver<LoaderKind> for LoaderVersionResolver {
         }
     }
 }
+
+impl LoaderVersionResolver {
+    /// Resolves the latest installer version used to build the server jar of a loader.
+    pub async fn resolve_installer(context: &McContext, loader: LoaderKind) -> McResult<String> {
+        match loader {
+            LoaderKind::Fabric => Ok(services::fabric_api::get_latest_installer(
+                &context.http_client
+            )
+            .await?
+            .version)
+        }
+    }
+}
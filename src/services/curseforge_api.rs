@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
+use crate::mods::loader::LoaderKind;
+use crate::mods::service::ModDependency;
+use crate::mods::service::ModDependencyKind;
+use crate::mods::service::ModFile;
+use crate::mods::service::ModProject;
+use crate::mods::service::ModService;
+use crate::mods::service::ModSideSupport;
+use crate::mods::service::ModVersion;
+use crate::utils::errors::McResult;
+
+/// The environment variable holding the key used to access the CurseForge API
+/// (https://console.curseforge.com).
+pub const API_KEY_VARIABLE: &str = "CURSEFORGE_API_KEY";
+
+const MINECRAFT_GAME_ID: &str = "432";
+const MODS_CLASS_ID: &str = "6";
+
+#[derive(Deserialize)]
+struct CurseForgeApiResponse<T> {
+    data: T
+}
+
+#[derive(Deserialize, Clone)]
+pub struct CurseForgeApiMod {
+    pub id: u64,
+    pub slug: String
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeApiFile {
+    pub id: u64,
+    pub mod_id: u64,
+    pub display_name: String,
+    pub hashes: Vec<CurseForgeApiFileHash>,
+    pub download_url: Option<Url>,
+
+    /// Game versions mixed with the names of the loaders, like `1.20.1` and `Fabric`.
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+
+    pub dependencies: Vec<CurseForgeApiDependency>
+}
+
+#[derive(Deserialize)]
+pub struct CurseForgeApiFileHash {
+    pub value: String,
+    pub algo: u8
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeApiDependency {
+    pub mod_id: u64,
+    pub relation_type: CurseForgeApiRelationKind
+}
+
+#[derive(Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(from = "u8")]
+pub enum CurseForgeApiRelationKind {
+    Optional,
+    Required,
+    Incompatible,
+    Other
+}
+
+impl From<u8> for CurseForgeApiRelationKind {
+    fn from(value: u8) -> Self {
+        match value {
+            2 => CurseForgeApiRelationKind::Optional,
+            3 => CurseForgeApiRelationKind::Required,
+            5 => CurseForgeApiRelationKind::Incompatible,
+            _ => CurseForgeApiRelationKind::Other
+        }
+    }
+}
+
+impl CurseForgeApiFile {
+    /// The strongest checksum provided for this file.
+    pub fn checksum(&self) -> McResult<LocalChecksum> {
+        let (algorithm, hash) = self
+            .hashes
+            .iter()
+            .find(|h| h.algo == 1)
+            .map(|h| (ChecksumAlgorithm::sha1, h))
+            .or_else(|| {
+                self.hashes
+                    .iter()
+                    .find(|h| h.algo == 2)
+                    .map(|h| (ChecksumAlgorithm::md5, h))
+            })
+            .ok_or_else(|| anyhow::anyhow!("could not find a hash for {}", self.display_name))?;
+
+        LocalChecksum::from_hex(algorithm, &hash.value)
+    }
+
+    /// Authors can disable downloads from third party tools, those files can't be installed.
+    pub fn url(&self) -> McResult<Url> {
+        self.download_url.clone().ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} can only be downloaded from the curseforge website, its author disabled third party downloads",
+                self.display_name
+            )
+        })
+    }
+}
+
+pub fn has_api_key() -> bool {
+    std::env::var(API_KEY_VARIABLE).is_ok_and(|k| !k.is_empty())
+}
+
+fn api_key() -> McResult<String> {
+    std::env::var(API_KEY_VARIABLE)
+        .ok()
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "a curseforge api key is required to use curseforge mods, set it in {}",
+                API_KEY_VARIABLE
+            )
+        })
+}
+
+fn loader_type(loader: LoaderKind) -> &'static str {
+    match loader {
+        LoaderKind::Fabric => "4"
+    }
+}
+
+async fn get<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    path: &str,
+    query: &[(&str, &str)],
+    error: String
+) -> McResult<T> {
+    let url = Url::parse(&format!("https://api.curseforge.com/v1/{}", path))?;
+
+    let response = client
+        .get(url)
+        .header("x-api-key", api_key()?)
+        .query(query)
+        .send()
+        .await?
+        .error_for_status()
+        .context(error)?
+        .json::<CurseForgeApiResponse<T>>()
+        .await?;
+
+    Ok(response.data)
+}
+
+pub async fn get_mod(client: &reqwest::Client, id: u64) -> McResult<CurseForgeApiMod> {
+    get(
+        client,
+        &format!("mods/{}", id),
+        &[],
+        format!("could not find curseforge mod with id {}", id)
+    )
+    .await
+}
+
+pub async fn get_mod_by_slug(client: &reqwest::Client, slug: &str) -> McResult<CurseForgeApiMod> {
+    let query = [
+        ("gameId", MINECRAFT_GAME_ID),
+        ("classId", MODS_CLASS_ID),
+        ("slug", slug)
+    ];
+
+    get::<Vec<CurseForgeApiMod>>(
+        client,
+        "mods/search",
+        &query,
+        format!("could not search curseforge for {}", slug)
+    )
+    .await?
+    .into_iter()
+    .find(|m| m.slug == slug)
+    .ok_or_else(|| anyhow::anyhow!("could not find curseforge mod {}", slug))
+}
+
+pub async fn get_file(
+    client: &reqwest::Client,
+    mod_id: u64,
+    file_id: u64
+) -> McResult<CurseForgeApiFile> {
+    get(
+        client,
+        &format!("mods/{}/files/{}", mod_id, file_id),
+        &[],
+        format!(
+            "could not find curseforge file {} of mod {}",
+            file_id, mod_id
+        )
+    )
+    .await
+}
+
+/// Lists the files of `mod_id` for a loader and game version, newest first.
+pub async fn get_files(
+    client: &reqwest::Client,
+    mod_id: u64,
+    loader: LoaderKind,
+    game_version: Option<&str>
+) -> McResult<Vec<CurseForgeApiFile>> {
+    let mut query = vec![("modLoaderType", loader_type(loader))];
+
+    if let Some(game_version) = game_version {
+        query.push(("gameVersion", game_version));
+    }
+
+    get(
+        client,
+        &format!("mods/{}/files", mod_id),
+        &query,
+        format!("could not find files of curseforge mod {}", mod_id)
+    )
+    .await
+}
+
+pub async fn get_latest_file(
+    client: &reqwest::Client,
+    mod_id: u64,
+    loader: LoaderKind,
+    game_version: Option<&str>
+) -> McResult<CurseForgeApiFile> {
+    get_files(client, mod_id, loader, game_version)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "could not find a suitable file of curseforge mod {}",
+                mod_id
+            )
+        })
+}
+
+pub struct CurseForgeApi {
+    client: reqwest::Client
+}
+
+impl CurseForgeApi {
+    pub fn new(client: reqwest::Client) -> CurseForgeApi {
+        CurseForgeApi { client }
+    }
+}
+
+impl ModService for CurseForgeApi {
+    async fn get_project(&self, id: &str) -> McResult<ModProject> {
+        let project = match id.parse::<u64>() {
+            Ok(id) => get_mod(&self.client, id).await?,
+            Err(_) => get_mod_by_slug(&self.client, id).await?
+        };
+
+        Ok(ModProject {
+            id: project.id.to_string(),
+            slug: project.slug,
+            // curseforge does not tell which side a mod runs on
+            server_side: ModSideSupport::Unknown
+        })
+    }
+
+    async fn get_version(&self, project: &str, id: &str) -> McResult<ModVersion> {
+        let file_id = id.parse::<u64>().map_err(|_| {
+            anyhow::anyhow!(
+                "curseforge versions are file ids, found {} for {}",
+                id,
+                project
+            )
+        })?;
+
+        let mod_id = mod_id(&self.client, project).await?;
+
+        get_file(&self.client, mod_id, file_id).await?.try_into()
+    }
+
+    async fn get_latest_version(
+        &self,
+        project: &str,
+        loader: LoaderKind,
+        game_version: Option<&str>
+    ) -> McResult<ModVersion> {
+        let mod_id = mod_id(&self.client, project).await?;
+
+        get_latest_file(&self.client, mod_id, loader, game_version)
+            .await?
+            .try_into()
+    }
+
+    /// CurseForge identifies files by a murmur2 fingerprint instead of a hash, no file is found.
+    async fn get_versions_from_hashes(
+        &self,
+        _hashes: &[String],
+        _algorithm: ChecksumAlgorithm
+    ) -> McResult<HashMap<String, ModVersion>> {
+        Ok(HashMap::new())
+    }
+}
+
+/// The id of a mod given by id or slug.
+async fn mod_id(client: &reqwest::Client, project: &str) -> McResult<u64> {
+    match project.parse::<u64>() {
+        Ok(id) => Ok(id),
+        Err(_) => Ok(get_mod_by_slug(client, project).await?.id)
+    }
+}
+
+impl TryFrom<CurseForgeApiFile> for ModVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(file: CurseForgeApiFile) -> Result<Self, Self::Error> {
+        let dependencies = file
+            .dependencies
+            .iter()
+            .filter_map(|d| {
+                let kind = match d.relation_type {
+                    CurseForgeApiRelationKind::Required => ModDependencyKind::Required,
+                    CurseForgeApiRelationKind::Optional => ModDependencyKind::Optional,
+                    CurseForgeApiRelationKind::Incompatible => ModDependencyKind::Incompatible,
+                    CurseForgeApiRelationKind::Other => return None
+                };
+
+                Some(ModDependency {
+                    project_id: d.mod_id.to_string(),
+                    version_id: None,
+                    kind
+                })
+            })
+            .collect();
+
+        let (game_versions, loaders): (Vec<String>, Vec<String>) = file
+            .game_versions
+            .iter()
+            .cloned()
+            .partition(|v| v.starts_with(|c: char| c.is_ascii_digit()));
+
+        Ok(ModVersion {
+            id: file.id.to_string(),
+            project_id: file.mod_id.to_string(),
+            version_number: file.display_name.clone(),
+            loaders: loaders.iter().map(|l| l.to_lowercase()).collect(),
+            game_versions,
+            file: ModFile {
+                url: file.url()?,
+                hash: file.checksum()?
+            },
+            dependencies
+        })
+    }
+}
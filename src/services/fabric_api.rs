@@ -26,13 +26,11 @@ pub struct FabricApiInstallerVersion {
     pub version: String
 }
 
-pub async fn artifact_source(
-    client: &reqwest::Client,
+pub fn artifact_source(
     loader: &ProductDescriptor<LoaderKind>,
-    version: &String
+    version: &String,
+    installer: &String
 ) -> McResult<ArtifactSource> {
-    let installer = get_latest_installer(client).await?.version;
-
     let url = format!(
         "https://meta.fabricmc.net/v2/versions/loader/{}/{}/{}/server/jar",
         version, loader.version, installer
@@ -0,0 +1,53 @@
+use anyhow::Context;
+use serde::Deserialize;
+use url::Url;
+
+use crate::utils::errors::McResult;
+
+#[derive(Deserialize)]
+pub struct GitHubApiRelease {
+    pub tag_name: String,
+    pub assets: Vec<GitHubApiAsset>
+}
+
+#[derive(Deserialize)]
+pub struct GitHubApiAsset {
+    pub name: String,
+    pub browser_download_url: Url
+}
+
+impl GitHubApiRelease {
+    /// The mod jar of the release, ignoring the sources and development jars.
+    pub fn mod_jar(&self) -> Option<&GitHubApiAsset> {
+        self.assets.iter().find(|a| {
+            a.name.ends_with(".jar")
+                && !a.name.ends_with("-sources.jar")
+                && !a.name.ends_with("-dev.jar")
+                && !a.name.ends_with("-javadoc.jar")
+        })
+    }
+}
+
+pub async fn get_latest_release(
+    client: &reqwest::Client,
+    repository: &String
+) -> McResult<GitHubApiRelease> {
+    let url = Url::parse(&format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        repository
+    ))?;
+
+    let release = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()
+        .context(format!(
+            "could not find a release of {} on github",
+            repository
+        ))?
+        .json::<GitHubApiRelease>()
+        .await?;
+
+    Ok(release)
+}
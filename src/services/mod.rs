@@ -1,5 +1,7 @@
 pub mod corretto_api;
+pub mod curseforge_api;
 pub mod fabric_api;
+pub mod github_api;
 pub mod graal_api;
 pub mod java_provider;
 pub mod minecraft_api;
@@ -1,13 +1,34 @@
+use std::collections::HashMap;
+
 use anyhow::Context;
 use serde::Deserialize;
+use serde::Serialize;
 use url::Url;
 
+use crate::crypto::checksum::ChecksumAlgorithm;
+use crate::crypto::checksum::LocalChecksum;
 use crate::mods::loader::LoaderKind;
+use crate::mods::service::ModDependency;
+use crate::mods::service::ModDependencyKind;
+use crate::mods::service::ModFile;
+use crate::mods::service::ModProject;
+use crate::mods::service::ModService;
+use crate::mods::service::ModSideSupport;
+use crate::mods::service::ModVersion;
 use crate::utils::errors::McResult;
 
 #[derive(Deserialize)]
 pub struct ModrinthApiVersion {
     pub id: String,
+    pub project_id: String,
+    pub version_number: String,
+
+    #[serde(default)]
+    pub loaders: Vec<String>,
+
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+
     pub dependencies: Vec<ModrinthApiDependency>,
     pub files: Vec<ModrinthApiFile>
 }
@@ -19,9 +40,20 @@ pub struct ModrinthApiFile {
     pub primary: bool
 }
 
+impl ModrinthApiFile {
+    /// The strongest checksum provided for this file.
+    pub fn checksum(&self) -> McResult<LocalChecksum> {
+        match self.hashes.sha512 {
+            Some(ref sha512) => LocalChecksum::from_hex(ChecksumAlgorithm::sha512, sha512),
+            None => LocalChecksum::from_hex(ChecksumAlgorithm::sha1, &self.hashes.sha1)
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ModrinthApiFileHashes {
-    pub sha1: String
+    pub sha1: String,
+    pub sha512: Option<String>
 }
 
 #[derive(Deserialize)]
@@ -39,12 +71,70 @@ pub enum ModrinthApiDependencyKind {
     Optional
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct ModrinthApiProject {
-    pub slug: String
+    pub id: String,
+    pub slug: String,
+
+    #[serde(default)]
+    pub client_side: ModrinthApiSideSupport,
+
+    #[serde(default)]
+    pub server_side: ModrinthApiSideSupport
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModrinthApiSideSupport {
+    Required,
+    Optional,
+    Unsupported,
+    #[default]
+    #[serde(other)]
+    Unknown
+}
+
+#[derive(Deserialize)]
+pub struct ModrinthApiSearchResults {
+    pub hits: Vec<ModrinthApiSearchHit>
+}
+
+#[derive(Deserialize)]
+pub struct ModrinthApiSearchHit {
+    pub slug: String,
+    pub description: String,
+    pub downloads: u64
+}
+
+/// Searches modrinth, `facets` are and-ed together while the values of each facet are or-ed.
+pub async fn search(
+    client: &reqwest::Client,
+    query: &str,
+    facets: &[Vec<String>],
+    limit: usize
+) -> McResult<ModrinthApiSearchResults> {
+    let url = Url::parse("https://api.modrinth.com/v2/search")?;
+
+    let query = vec![
+        ("query", query.to_string()),
+        ("facets", serde_json::to_string(facets)?),
+        ("limit", limit.to_string()),
+    ];
+
+    let results = client
+        .get(url)
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()
+        .context("could not search modrinth")?
+        .json::<ModrinthApiSearchResults>()
+        .await?;
+
+    Ok(results)
 }
 
-pub async fn get_project(client: &reqwest::Client, id: &String) -> McResult<ModrinthApiProject> {
+pub async fn get_project(client: &reqwest::Client, id: &str) -> McResult<ModrinthApiProject> {
     let url = Url::parse(&format!("https://api.modrinth.com/v2/project/{}", id))?;
 
     let project = client
@@ -74,36 +164,259 @@ pub async fn get_version(client: &reqwest::Client, id: &String) -> McResult<Modr
     Ok(version)
 }
 
-pub async fn get_latest_version(
+/// Finds the versions owning the files with the given `hashes`, keyed by hash. Hashes that are
+/// not known by modrinth are left out.
+pub async fn get_versions_from_hashes(
     client: &reqwest::Client,
-    project: &String,
-    loader: LoaderKind,
-    game_version: &String
+    hashes: &[String],
+    algorithm: ChecksumAlgorithm
+) -> McResult<HashMap<String, ModrinthApiVersion>> {
+    let url = Url::parse("https://api.modrinth.com/v2/version_files")?;
+
+    let body = serde_json::json!({
+        "hashes": hashes,
+        "algorithm": algorithm.to_string()
+    });
+
+    let versions = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()
+        .context("could not look up files on modrinth")?
+        .json::<HashMap<String, ModrinthApiVersion>>()
+        .await?;
+
+    Ok(versions)
+}
+
+/// Finds a version of `project` by its id or its version number.
+pub async fn get_project_version(
+    client: &reqwest::Client,
+    project: &str,
+    version: &str
 ) -> McResult<ModrinthApiVersion> {
+    let url = Url::parse(&format!(
+        "https://api.modrinth.com/v2/project/{}/version/{}",
+        project, version
+    ))?;
+
+    let version = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()
+        .context(format!("could not find version {} of {}", version, project))?
+        .json::<ModrinthApiVersion>()
+        .await?;
+
+    Ok(version)
+}
+
+/// Lists every version of `project`, newest first.
+pub async fn get_project_versions(
+    client: &reqwest::Client,
+    project: &String
+) -> McResult<Vec<ModrinthApiVersion>> {
     let url = Url::parse(&format!(
         "https://api.modrinth.com/v2/project/{}/version",
         project
     ))?;
 
-    let loaders = serde_json::to_string(&vec![loader])?;
-    let game_versions = serde_json::to_string(&vec![game_version])?;
-
     let versions = client
         .get(url)
-        .query(&[
-            ("loaders", loaders.as_str()),
-            ("game_versions", game_versions.as_str()),
-            ("include_changelog", "false")
-        ])
+        .query(&[("include_changelog", "false")])
         .send()
         .await?
         .error_for_status()
-        .context(format!("could not find a suitable version of {}", project))?
+        .context(format!("could not find versions of {}", project))?
         .json::<Vec<ModrinthApiVersion>>()
         .await?;
 
-    versions
+    Ok(versions)
+}
+
+pub async fn get_versions(
+    client: &reqwest::Client,
+    project: &str,
+    loader: LoaderKind,
+    game_version: Option<&str>
+) -> McResult<Vec<ModrinthApiVersion>> {
+    get_versions_for_loader(client, project, &loader.to_string(), game_version).await
+}
+
+/// The newest version of `project` for `game_version` published under `loader`, datapacks use
+/// the `datapack` loader and resource packs the `minecraft` one.
+pub async fn get_latest_version_for_loader(
+    client: &reqwest::Client,
+    project: &str,
+    loader: &str,
+    game_version: &str
+) -> McResult<ModrinthApiVersion> {
+    get_versions_for_loader(client, project, loader, Some(game_version))
+        .await
+        .context(format!("could not find a suitable version of {}", project))?
         .into_iter()
         .next()
         .ok_or_else(|| anyhow::anyhow!("could not find a suitable version of {}", project))
 }
+
+async fn get_versions_for_loader(
+    client: &reqwest::Client,
+    project: &str,
+    loader: &str,
+    game_version: Option<&str>
+) -> McResult<Vec<ModrinthApiVersion>> {
+    let url = Url::parse(&format!(
+        "https://api.modrinth.com/v2/project/{}/version",
+        project
+    ))?;
+
+    let loaders = serde_json::to_string(&vec![loader])?;
+
+    let mut query = vec![
+        ("loaders", loaders),
+        ("include_changelog", String::from("false")),
+    ];
+
+    if let Some(game_version) = game_version {
+        query.push(("game_versions", serde_json::to_string(&vec![game_version])?));
+    }
+
+    let versions = client
+        .get(url)
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()
+        .context(format!("could not find versions of {}", project))?
+        .json::<Vec<ModrinthApiVersion>>()
+        .await?;
+
+    Ok(versions)
+}
+
+pub async fn get_latest_version(
+    client: &reqwest::Client,
+    project: &str,
+    loader: LoaderKind,
+    game_version: &str
+) -> McResult<ModrinthApiVersion> {
+    get_latest_version_for_loader(client, project, &loader.to_string(), game_version).await
+}
+
+pub struct ModrinthApi {
+    client: reqwest::Client
+}
+
+impl ModrinthApi {
+    pub fn new(client: reqwest::Client) -> ModrinthApi {
+        ModrinthApi { client }
+    }
+}
+
+impl ModService for ModrinthApi {
+    async fn get_project(&self, id: &str) -> McResult<ModProject> {
+        Ok(get_project(&self.client, id).await?.into())
+    }
+
+    async fn get_version(&self, project: &str, id: &str) -> McResult<ModVersion> {
+        get_project_version(&self.client, project, id)
+            .await?
+            .try_into()
+    }
+
+    async fn get_latest_version(
+        &self,
+        project: &str,
+        loader: LoaderKind,
+        game_version: Option<&str>
+    ) -> McResult<ModVersion> {
+        get_versions(&self.client, project, loader, game_version)
+            .await
+            .context(format!("could not find a suitable version of {}", project))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("could not find a suitable version of {}", project))?
+            .try_into()
+    }
+
+    async fn get_versions_from_hashes(
+        &self,
+        hashes: &[String],
+        algorithm: ChecksumAlgorithm
+    ) -> McResult<HashMap<String, ModVersion>> {
+        get_versions_from_hashes(&self.client, hashes, algorithm)
+            .await?
+            .into_iter()
+            .map(|(hash, version)| Ok((hash, version.try_into()?)))
+            .collect()
+    }
+}
+
+impl From<ModrinthApiProject> for ModProject {
+    fn from(project: ModrinthApiProject) -> Self {
+        ModProject {
+            id: project.id,
+            slug: project.slug,
+            server_side: project.server_side.into()
+        }
+    }
+}
+
+impl From<ModrinthApiSideSupport> for ModSideSupport {
+    fn from(side: ModrinthApiSideSupport) -> Self {
+        match side {
+            ModrinthApiSideSupport::Required => ModSideSupport::Required,
+            ModrinthApiSideSupport::Optional => ModSideSupport::Optional,
+            ModrinthApiSideSupport::Unsupported => ModSideSupport::Unsupported,
+            ModrinthApiSideSupport::Unknown => ModSideSupport::Unknown
+        }
+    }
+}
+
+impl From<ModrinthApiDependencyKind> for ModDependencyKind {
+    fn from(kind: ModrinthApiDependencyKind) -> Self {
+        match kind {
+            ModrinthApiDependencyKind::Required => ModDependencyKind::Required,
+            ModrinthApiDependencyKind::Incompatible => ModDependencyKind::Incompatible,
+            ModrinthApiDependencyKind::Optional => ModDependencyKind::Optional
+        }
+    }
+}
+
+impl TryFrom<ModrinthApiVersion> for ModVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: ModrinthApiVersion) -> Result<Self, Self::Error> {
+        let file = version.files.iter().find(|f| f.primary).ok_or_else(|| {
+            anyhow::anyhow!("could not find a file to install for {}", version.id)
+        })?;
+
+        let file = ModFile {
+            url: file.url.clone(),
+            hash: file.checksum()?
+        };
+
+        let dependencies = version
+            .dependencies
+            .into_iter()
+            .map(|d| ModDependency {
+                project_id: d.project_id,
+                version_id: d.version_id,
+                kind: d.dependency_type.into()
+            })
+            .collect();
+
+        Ok(ModVersion {
+            id: version.id,
+            project_id: version.project_id,
+            version_number: version.version_number,
+            loaders: version.loaders,
+            game_versions: version.game_versions,
+            dependencies,
+            file
+        })
+    }
+}
@@ -32,6 +32,12 @@ impl<T, const SEP: char> From<Vec<T>> for SeparatedList<T, SEP> {
     }
 }
 
+impl<T, const SEP: char> SeparatedList<T, SEP> {
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+    }
+}
+
 impl<T, const SEP: char> AsRef<[T]> for SeparatedList<T, SEP> {
     fn as_ref(&self) -> &[T] {
         &self.data
@@ -0,0 +1,23 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::utils::errors::McResult;
+
+/// Lists the files under `directory`, recursively.
+pub fn walk(directory: &Path) -> McResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(walk(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
@@ -2,6 +2,7 @@ mod case_iterable;
 pub mod csv;
 pub mod date;
 pub mod errors;
+pub mod fs;
 pub mod product_descriptor;
 pub mod restricted_names;
 pub mod shell;